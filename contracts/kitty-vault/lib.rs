@@ -0,0 +1,168 @@
+//! A vault holding kitties on behalf of its admin, who can list them for sale or send them out.
+//!
+//! It shows how a contract reaches `pallet_kitties` through the chain extension of the runtime,
+//! see `runtime/src/chain_extension.rs`. Build it with `cargo contract build`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_env::{AccountId, DefaultEnvironment, Environment};
+use ink_lang as ink;
+
+pub type KittyIndex = u32;
+pub type Dna = [u8; 16];
+
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// The functions of the kitties chain extension.
+#[ink::chain_extension]
+pub trait Kitties {
+	type ErrorCode = KittiesError;
+
+	#[ink(extension = 1, handle_status = false, returns_result = false)]
+	fn owns(owner: AccountId, kitty_id: KittyIndex) -> bool;
+
+	#[ink(extension = 2, handle_status = false, returns_result = false)]
+	fn dna(owner: AccountId, kitty_id: KittyIndex) -> Option<Dna>;
+
+	#[ink(extension = 3, handle_status = false, returns_result = false)]
+	fn price(kitty_id: KittyIndex) -> Option<Balance>;
+
+	#[ink(extension = 4, returns_result = false)]
+	fn transfer(to: AccountId, kitty_id: KittyIndex);
+
+	#[ink(extension = 5, returns_result = false)]
+	fn set_price(kitty_id: KittyIndex, new_price: Option<Balance>);
+
+	#[ink(extension = 6, handle_status = false, returns_result = false)]
+	fn owner_of(kitty_id: KittyIndex) -> Option<AccountId>;
+}
+
+/// The status codes of `transfer` and `set_price`, see `KittiesStatus` in the runtime.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum KittiesError {
+	NotOwner,
+	TradingPaused,
+	Failed,
+}
+
+impl ink_env::chain_extension::FromStatusCode for KittiesError {
+	fn from_status_code(status_code: u32) -> Result<(), Self> {
+		match status_code {
+			0 => Ok(()),
+			1 => Err(Self::NotOwner),
+			2 => Err(Self::TradingPaused),
+			_ => Err(Self::Failed),
+		}
+	}
+}
+
+/// The default environment with the kitties chain extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum KittiesEnvironment {}
+
+impl Environment for KittiesEnvironment {
+	const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+	type AccountId = <DefaultEnvironment as Environment>::AccountId;
+	type Balance = <DefaultEnvironment as Environment>::Balance;
+	type Hash = <DefaultEnvironment as Environment>::Hash;
+	type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+	type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+
+	type ChainExtension = Kitties;
+}
+
+#[ink::contract(env = crate::KittiesEnvironment)]
+mod kitty_vault {
+	use super::{Dna, KittiesError, KittyIndex};
+
+	#[ink(storage)]
+	pub struct KittyVault {
+		admin: AccountId,
+	}
+
+	#[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+	#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+	pub enum Error {
+		/// The caller is not the admin of the vault.
+		NotAdmin,
+		/// The kitties pallet rejected the call.
+		Kitties(KittiesError),
+	}
+
+	impl From<KittiesError> for Error {
+		fn from(error: KittiesError) -> Self {
+			Error::Kitties(error)
+		}
+	}
+
+	/// A kitty left the vault.
+	#[ink(event)]
+	pub struct Sent {
+		#[ink(topic)]
+		to: AccountId,
+		kitty_id: KittyIndex,
+	}
+
+	impl KittyVault {
+		/// Create a vault administered by the caller.
+		#[ink(constructor)]
+		pub fn new() -> Self {
+			Self { admin: Self::env().caller() }
+		}
+
+		/// Whether the vault holds `kitty_id`.
+		#[ink(message)]
+		pub fn holds(&self, kitty_id: KittyIndex) -> bool {
+			self.env().extension().owns(self.env().account_id(), kitty_id)
+		}
+
+		/// The DNA of `kitty_id`, if the vault holds it.
+		#[ink(message)]
+		pub fn dna(&self, kitty_id: KittyIndex) -> Option<Dna> {
+			self.env().extension().dna(self.env().account_id(), kitty_id)
+		}
+
+		/// The owner of `kitty_id`, if it exists.
+		#[ink(message)]
+		pub fn owner_of(&self, kitty_id: KittyIndex) -> Option<AccountId> {
+			self.env().extension().owner_of(kitty_id)
+		}
+
+		/// The price `kitty_id` is listed for.
+		#[ink(message)]
+		pub fn price(&self, kitty_id: KittyIndex) -> Option<Balance> {
+			self.env().extension().price(kitty_id)
+		}
+
+		/// List `kitty_id` for `price`, or delist it with `None`. Only the admin can do this.
+		#[ink(message)]
+		pub fn list(&mut self, kitty_id: KittyIndex, price: Option<Balance>) -> Result<(), Error> {
+			self.ensure_admin()?;
+			self.env().extension().set_price(kitty_id, price)?;
+
+			Ok(())
+		}
+
+		/// Send `kitty_id` out of the vault to `to`. Only the admin can do this.
+		#[ink(message)]
+		pub fn send(&mut self, to: AccountId, kitty_id: KittyIndex) -> Result<(), Error> {
+			self.ensure_admin()?;
+			self.env().extension().transfer(to, kitty_id)?;
+
+			self.env().emit_event(Sent { to, kitty_id });
+
+			Ok(())
+		}
+
+		fn ensure_admin(&self) -> Result<(), Error> {
+			if self.env().caller() == self.admin {
+				Ok(())
+			} else {
+				Err(Error::NotAdmin)
+			}
+		}
+	}
+}
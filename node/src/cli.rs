@@ -1,4 +1,6 @@
+use crate::service::Sealing;
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +9,23 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks with manual seal instead of Aura and GRANDPA, for development: `instant`
+	/// seals a block per transaction, `manual` one per `engine_createBlock` call, and a number
+	/// seals one every that many milliseconds. Only allowed on a development chain.
+	#[clap(long)]
+	pub sealing: Option<Sealing>,
+
+	/// The file holding the shared secret token of the remote signer given by `--keystore-uri`.
+	#[clap(long, value_name = "PATH", parse(from_os_str))]
+	pub keystore_token_file: Option<PathBuf>,
+
+	/// Index the kitty events in the SQLite database at this path, and serve the history of each
+	/// kitty with the `kitties_history` RPC.
+	///
+	/// Needs the state of every block, so the node must run with `--pruning archive`.
+	#[clap(long, value_name = "PATH", parse(from_os_str))]
+	pub kitty_index: Option<PathBuf>,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -36,7 +55,19 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Read the kitties from the local database.
+	#[clap(subcommand)]
+	Kitties(crate::kitties::KittiesSubcommand),
+
 	/// The custom benchmark subcommand benchmarking runtime pallets.
 	#[clap(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Try some command against runtime state.
+	#[cfg(feature = "try-runtime")]
+	TryRuntime(try_runtime_cli::TryRuntimeCmd),
+
+	/// Try some command against runtime state. Note: `try-runtime` feature must be enabled.
+	#[cfg(not(feature = "try-runtime"))]
+	TryRuntime,
 }
@@ -0,0 +1,385 @@
+//! An optional SQLite index of the kitty events, which keeps the ownership and sale history of
+//! every kitty for the `kitties_history` RPC.
+//!
+//! The index reads the events from the state of each block, so it needs an archive node. It
+//! follows the best chain, indexing each block once. The hashes of the indexed blocks
+//! above the last finalized one are kept, so that on a reorg only the events of the retracted
+//! blocks are removed before the new best chain is indexed from the common ancestor.
+
+use codec::Decode;
+use futures::{future, stream, StreamExt};
+use node_template_runtime::{opaque::Block, AccountId, BlockNumber, Event, Hash, Runtime};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use serde::Serialize;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, storage::StorageKey};
+use sp_runtime::{generic::BlockId, traits::Header as _};
+use std::{
+	path::Path,
+	sync::{Arc, Mutex},
+};
+
+/// The kitty index of the runtime.
+type KittyIndex = u32;
+
+type KittyEvent = pallet_kitties::Event<Runtime>;
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS ownership (
+		kitty_id INTEGER NOT NULL,
+		owner TEXT,
+		reason TEXT NOT NULL,
+		block_number INTEGER NOT NULL,
+		block_hash TEXT NOT NULL,
+		event_index INTEGER NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS ownership_by_kitty ON ownership (kitty_id);
+	CREATE INDEX IF NOT EXISTS ownership_by_block ON ownership (block_number);
+	CREATE TABLE IF NOT EXISTS sales (
+		kitty_id INTEGER NOT NULL,
+		seller TEXT NOT NULL,
+		buyer TEXT NOT NULL,
+		price TEXT NOT NULL,
+		block_number INTEGER NOT NULL,
+		block_hash TEXT NOT NULL,
+		event_index INTEGER NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS sales_by_kitty ON sales (kitty_id);
+	CREATE INDEX IF NOT EXISTS sales_by_block ON sales (block_number);
+	CREATE TABLE IF NOT EXISTS blocks (
+		number INTEGER PRIMARY KEY,
+		hash TEXT NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS meta (
+		key TEXT PRIMARY KEY,
+		value INTEGER NOT NULL
+	);
+";
+
+/// The `meta` key of the last finalized block that has been indexed.
+const FINALIZED: &str = "finalized";
+
+/// An error of the kitty index.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// The database could not be read or written.
+	#[error(transparent)]
+	Database(#[from] rusqlite::Error),
+	/// The blockchain could not be read.
+	#[error(transparent)]
+	Blockchain(#[from] sp_blockchain::Error),
+	/// A block of the best chain is missing.
+	#[error("Unknown block {0}")]
+	UnknownBlock(Hash),
+	/// The events of a block could not be decoded.
+	#[error("Invalid events at block {0}: {1}")]
+	InvalidEvents(Hash, codec::Error),
+}
+
+/// A change of owner of a kitty. A burned kitty has no owner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnershipRecord {
+	/// The SS58 address of the new owner.
+	pub owner: Option<String>,
+	/// Why the owner changed: `created`, `bred`, `claimed`, `transferred`, `sold`,
+	/// `force_transferred` or `burned`.
+	pub reason: String,
+	/// The number of the block of the change.
+	pub block_number: BlockNumber,
+	/// The hash of the block of the change.
+	pub block_hash: String,
+	/// The index of the event among the events of the block.
+	pub event_index: u32,
+}
+
+/// A sale of a kitty. The price is a decimal string, as it may not fit in a JSON number.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaleRecord {
+	/// The SS58 address of the seller.
+	pub seller: String,
+	/// The SS58 address of the buyer.
+	pub buyer: String,
+	/// The price paid by the buyer.
+	pub price: String,
+	/// The number of the block of the sale.
+	pub block_number: BlockNumber,
+	/// The hash of the block of the sale.
+	pub block_hash: String,
+	/// The index of the event among the events of the block.
+	pub event_index: u32,
+}
+
+/// The provenance of a kitty, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KittyHistory {
+	/// The kitty.
+	pub kitty_id: KittyIndex,
+	/// Every owner of the kitty, from its creation on.
+	pub owners: Vec<OwnershipRecord>,
+	/// Every sale of the kitty.
+	pub sales: Vec<SaleRecord>,
+}
+
+/// The SQLite database of the kitty index.
+pub struct KittyIndexDb {
+	connection: Mutex<Connection>,
+}
+
+impl KittyIndexDb {
+	/// Open the index at `path`, creating it if needed.
+	pub fn open(path: &Path) -> Result<Self, Error> {
+		let connection = Connection::open(path)?;
+		connection.execute_batch(SCHEMA)?;
+
+		Ok(Self { connection: Mutex::new(connection) })
+	}
+
+	/// The ownership and sale history of `kitty_id`.
+	pub fn history(&self, kitty_id: KittyIndex) -> Result<KittyHistory, Error> {
+		let connection = self.connection.lock().expect("the index lock is never poisoned; qed");
+
+		let owners = connection
+			.prepare(
+				"SELECT owner, reason, block_number, block_hash, event_index FROM ownership
+				 WHERE kitty_id = ?1 ORDER BY block_number, event_index",
+			)?
+			.query_map(params![kitty_id], |row| {
+				Ok(OwnershipRecord {
+					owner: row.get(0)?,
+					reason: row.get(1)?,
+					block_number: row.get(2)?,
+					block_hash: row.get(3)?,
+					event_index: row.get(4)?,
+				})
+			})?
+			.collect::<Result<_, _>>()?;
+
+		let sales = connection
+			.prepare(
+				"SELECT seller, buyer, price, block_number, block_hash, event_index FROM sales
+				 WHERE kitty_id = ?1 ORDER BY block_number, event_index",
+			)?
+			.query_map(params![kitty_id], |row| {
+				Ok(SaleRecord {
+					seller: row.get(0)?,
+					buyer: row.get(1)?,
+					price: row.get(2)?,
+					block_number: row.get(3)?,
+					block_hash: row.get(4)?,
+					event_index: row.get(5)?,
+				})
+			})?
+			.collect::<Result<_, _>>()?;
+
+		Ok(KittyHistory { kitty_id, owners, sales })
+	}
+
+	/// Index the best chain of `client` up to its best block.
+	fn sync<BE, C>(&self, client: &C) -> Result<(), Error>
+	where
+		BE: Backend<Block>,
+		C: HeaderBackend<Block> + StorageProvider<Block, BE>,
+	{
+		let info = client.info();
+		let mut connection = self.connection.lock().expect("the index lock is never poisoned; qed");
+		let tx = connection.transaction()?;
+
+		let finalized: Option<BlockNumber> = tx
+			.query_row("SELECT value FROM meta WHERE key = ?1", params![FINALIZED], |row| {
+				row.get(0)
+			})
+			.optional()?;
+
+		// walk back from the best block to the last block that is still indexed
+		let mut blocks = Vec::new();
+		let mut hash = info.best_hash;
+		let ancestor = loop {
+			let header = client.header(BlockId::Hash(hash))?.ok_or(Error::UnknownBlock(hash))?;
+			let number = *header.number();
+			if finalized.map_or(false, |finalized| number <= finalized) ||
+				indexed_hash(&tx, number)?.as_ref() == Some(&format!("{:?}", hash))
+			{
+				break Some(number)
+			}
+			blocks.push((number, hash));
+			if number == 0 {
+				break None
+			}
+			hash = *header.parent_hash();
+		};
+
+		// the indexed blocks above the common ancestor have been retracted
+		let above = ancestor.map_or(-1, i64::from);
+		tx.execute("DELETE FROM ownership WHERE block_number > ?1", params![above])?;
+		tx.execute("DELETE FROM sales WHERE block_number > ?1", params![above])?;
+		tx.execute("DELETE FROM blocks WHERE number > ?1", params![above])?;
+
+		for (number, hash) in blocks.into_iter().rev() {
+			let events = match kitty_events(client, hash) {
+				Ok(events) => events,
+				// one bad block must not stop the index, so it is left out of the history
+				Err(e @ Error::InvalidEvents(..)) => {
+					log::warn!(target: "kitties-indexer", "Skipping block #{}: {}", number, e);
+					Vec::new()
+				},
+				Err(e) => return Err(e),
+			};
+			index_block(&tx, number, hash, events)?;
+			tx.execute(
+				"INSERT INTO blocks (number, hash) VALUES (?1, ?2)",
+				params![number, format!("{:?}", hash)],
+			)?;
+		}
+
+		// finalized blocks are never retracted, so only the hashes from the last one up are kept
+		let finalized = info.finalized_number.min(info.best_number);
+		tx.execute(
+			"INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+			params![FINALIZED, finalized],
+		)?;
+		tx.execute("DELETE FROM blocks WHERE number < ?1", params![finalized])?;
+		tx.commit()?;
+
+		Ok(())
+	}
+}
+
+/// The hash of the indexed block at `number`, unless it is below the last finalized block.
+fn indexed_hash(tx: &Transaction, number: BlockNumber) -> Result<Option<String>, Error> {
+	Ok(tx
+		.query_row("SELECT hash FROM blocks WHERE number = ?1", params![number], |row| row.get(0))
+		.optional()?)
+}
+
+/// Follow the imported and finalized blocks of `client`, indexing their kitty events in `index`.
+pub async fn run<BE, C>(client: Arc<C>, index: Arc<KittyIndexDb>)
+where
+	BE: Backend<Block>,
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, BE>,
+{
+	let notifications = stream::select(
+		client.import_notification_stream().map(|_| ()),
+		client.finality_notification_stream().map(|_| ()),
+	);
+
+	let sync = move || {
+		if let Err(e) = index.sync(&*client) {
+			log::warn!(target: "kitties-indexer", "Unable to index the kitty events: {}", e);
+		}
+	};
+
+	// catch up with the blocks imported while the index was not running
+	sync();
+	notifications
+		.for_each(|()| {
+			sync();
+			future::ready(())
+		})
+		.await
+}
+
+/// The kitty events of the block `hash`, with their index among all of its events.
+///
+/// A sale is reported by its `KittySold` event alone: the `KittyTransferred` event of the same
+/// move, which the pallet emits first, is left out.
+pub fn kitty_events<BE, C>(client: &C, hash: Hash) -> Result<Vec<(u32, KittyEvent)>, Error>
+where
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE>,
+{
+	let key = StorageKey(frame_support::storage::storage_prefix(b"System", b"Events").to_vec());
+	let records = match client.storage(&BlockId::Hash(hash), &key)? {
+		Some(value) => Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &value.0[..])
+			.map_err(|e| Error::InvalidEvents(hash, e))?,
+		None => Vec::new(),
+	};
+
+	let mut events: Vec<(u32, KittyEvent)> = Vec::new();
+	for (i, record) in records.into_iter().enumerate() {
+		if let Event::Kitties(event) = record.event {
+			if let KittyEvent::KittySold(seller, buyer, kitty_id, _) = &event {
+				if matches!(
+					events.last(),
+					Some((_, KittyEvent::KittyTransferred(from, to, id)))
+						if from == seller && to == buyer && id == kitty_id
+				) {
+					events.pop();
+				}
+			}
+			events.push((i as u32, event));
+		}
+	}
+
+	Ok(events)
+}
+
+/// Record the changes of owner and the sales of `events`.
+fn index_block(
+	tx: &Transaction,
+	number: BlockNumber,
+	hash: Hash,
+	events: Vec<(u32, KittyEvent)>,
+) -> Result<(), Error> {
+	let hash = format!("{:?}", hash);
+	let record_owner = |kitty_id: KittyIndex, owner: Option<&AccountId>, reason, i| {
+		tx.execute(
+			"INSERT INTO ownership (kitty_id, owner, reason, block_number, block_hash, event_index)
+			 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+			params![kitty_id, owner.map(Ss58Codec::to_ss58check), reason, number, hash, i],
+		)
+	};
+
+	for (i, event) in events {
+		match event {
+			KittyEvent::KittyCreated(owner, kitty_id, _) =>
+				record_owner(kitty_id, Some(&owner), "created", i)?,
+			KittyEvent::KittyCreatedByBreeding(owner, kitty_id, _) =>
+				record_owner(kitty_id, Some(&owner), "bred", i)?,
+			KittyEvent::KittyClaimed(owner, kitty_id, _) =>
+				record_owner(kitty_id, Some(&owner), "claimed", i)?,
+			KittyEvent::KittyTransferred(_, to, kitty_id) =>
+				record_owner(kitty_id, Some(&to), "transferred", i)?,
+			KittyEvent::KittyForceTransferred(_, to, kitty_id) =>
+				record_owner(kitty_id, Some(&to), "force_transferred", i)?,
+			KittyEvent::KittyForceBurned(_, kitty_id) => record_owner(kitty_id, None, "burned", i)?,
+			KittyEvent::KittySold(seller, buyer, kitty_id, price) => {
+				record_owner(kitty_id, Some(&buyer), "sold", i)?;
+				tx.execute(
+					"INSERT INTO sales (kitty_id, seller, buyer, price, block_number, block_hash,
+					 event_index) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+					params![
+						kitty_id,
+						seller.to_ss58check(),
+						buyer.to_ss58check(),
+						price.to_string(),
+						number,
+						hash,
+						i
+					],
+				)?
+			},
+			// the other events leave the owner of the kitty unchanged, and are listed one by one
+			// so that a new event has to be considered here
+			KittyEvent::EggLaid(..) |
+			KittyEvent::KittyApproved(..) |
+			KittyEvent::KittyPriceUpdated(..) |
+			KittyEvent::KittyForceDelisted(..) |
+			KittyEvent::PauseFlagsUpdated(..) |
+			KittyEvent::PresaleScheduled(..) |
+			KittyEvent::OrdersCancelled(..) |
+			KittyEvent::DnaCommitted(..) |
+			KittyEvent::CommitmentReaped(..) |
+			KittyEvent::MarketParamChanged(..) |
+			KittyEvent::ActionScheduled(..) |
+			KittyEvent::ActionCancelled(..) |
+			KittyEvent::ActionRun(..) => 0,
+			KittyEvent::__Ignore(..) => unreachable!("`__Ignore` is never emitted; qed"),
+		};
+	}
+
+	Ok(())
+}
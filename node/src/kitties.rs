@@ -0,0 +1,434 @@
+//! The `kitties` subcommands, which read the kitties from the local database.
+
+use codec::Decode;
+use frame_support::storage::StoragePrefixedMap;
+use node_template_runtime::{opaque::Block, AccountId, Balance, Runtime};
+use pallet_kitties::{Kitties, Kitty, KittyPrices, Owners, Parents};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, hexdisplay::HexDisplay, storage::StorageKey};
+use sp_runtime::generic::BlockId;
+use std::{
+	fs::File,
+	io::{self, Write},
+	path::PathBuf,
+	sync::Arc,
+};
+
+/// The kitty index of the runtime.
+type KittyIndex = u32;
+
+/// Read the kitties from the local database.
+#[derive(Debug, clap::Subcommand)]
+pub enum KittiesSubcommand {
+	/// Export every kitty to JSON or CSV.
+	Export(ExportKittiesCmd),
+
+	/// Print the gender, traits, lineage and picture of a kitty.
+	Inspect(InspectKittyCmd),
+}
+
+/// The format of the exported kitties.
+#[derive(Debug, Clone, Copy, clap::ArgEnum)]
+pub enum ExportFormat {
+	/// A JSON array of kitties.
+	Json,
+	/// A CSV table with a header row.
+	Csv,
+}
+
+/// The `kitties export` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct ExportKittiesCmd {
+	/// The block to export the kitties at, the best block by default.
+	#[clap(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// The format of the export.
+	#[clap(long, arg_enum, default_value = "json")]
+	pub format: ExportFormat,
+
+	/// The file to write to, the standard output by default.
+	#[clap(long, short, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// The `kitties inspect` command.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct InspectKittyCmd {
+	/// The id of a kitty on chain, or the DNA of a kitty as 0x-prefixed hex.
+	#[clap(value_name = "ID or DNA")]
+	pub kitty: String,
+
+	/// The block to read the kitty at, the best block by default.
+	#[clap(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// The number of generations of ancestors to print.
+	#[clap(long, default_value = "2")]
+	pub depth: u32,
+
+	/// Draw the picture without terminal colours.
+	#[clap(long)]
+	pub no_colour: bool,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+/// A kitty as exported, with its decoded traits. The price is a decimal string, as it may not fit
+/// in a JSON number.
+#[derive(Debug, serde::Serialize)]
+struct ExportedKitty {
+	id: KittyIndex,
+	owner: String,
+	dna: String,
+	gender: &'static str,
+	body_colour: &'static str,
+	pattern: &'static str,
+	eye_colour: &'static str,
+	price: Option<String>,
+}
+
+impl ExportKittiesCmd {
+	/// Write every kitty at the chosen block to the output.
+	pub fn run<BE, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		BE: Backend<Block>,
+		C: StorageProvider<Block, BE> + HeaderBackend<Block>,
+	{
+		let at = block_id(&*client, self.at.as_ref())?;
+
+		let mut kitties = Vec::new();
+		for (owner, kitty_id, kitty) in all_kitties(&*client, &at)? {
+			let traits = kitty.traits();
+			kitties.push(ExportedKitty {
+				id: kitty_id,
+				owner: owner.to_ss58check(),
+				dna: format!("0x{}", HexDisplay::from(&kitty.dna())),
+				gender: kitty.gender().name(),
+				body_colour: traits.body_colour_name(),
+				pattern: traits.pattern_name(),
+				eye_colour: traits.eye_colour_name(),
+				price: price_of(&*client, &at, kitty_id)?.map(|price| price.to_string()),
+			});
+		}
+		kitties.sort_by_key(|kitty| kitty.id);
+
+		let mut output: Box<dyn Write> = match &self.output {
+			Some(path) => Box::new(File::create(path)?),
+			None => Box::new(io::stdout()),
+		};
+		match self.format {
+			ExportFormat::Json => {
+				serde_json::to_writer_pretty(&mut output, &kitties)
+					.map_err(|e| format!("Failed to write JSON: {}", e))?;
+				writeln!(output)?;
+			},
+			ExportFormat::Csv => write_csv(&mut output, &kitties)?,
+		}
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportKittiesCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+impl InspectKittyCmd {
+	/// Print the kitty, reading it from the chain unless its DNA is given.
+	pub fn run<BE, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		BE: Backend<Block>,
+		C: StorageProvider<Block, BE> + HeaderBackend<Block>,
+	{
+		let mut output = io::stdout();
+
+		if self.kitty.starts_with("0x") {
+			let dna = sp_core::bytes::from_hex(&self.kitty)
+				.ok()
+				.and_then(|dna| dna.try_into().ok())
+				.ok_or("The DNA must be 16 bytes of 0x-prefixed hex")?;
+			let kitty = Kitty(dna);
+
+			write_kitty(&mut output, &kitty)?;
+			writeln!(output, "Lineage:    unknown")?;
+			writeln!(output)?;
+			write!(output, "{}", draw(&kitty, !self.no_colour))?;
+
+			return Ok(())
+		}
+
+		let kitty_id: KittyIndex =
+			self.kitty.parse().map_err(|_| "The kitty must be an id or 0x-prefixed DNA")?;
+		let at = block_id(&*client, self.at.as_ref())?;
+		let owner: AccountId =
+			storage_value(&*client, &at, Owners::<Runtime>::hashed_key_for(kitty_id))?
+				.ok_or_else(|| format!("Kitty {} does not exist", kitty_id))?;
+		let kitty: Kitty =
+			storage_value(&*client, &at, Kitties::<Runtime>::hashed_key_for(&owner, kitty_id))?
+				.ok_or_else(|| format!("Kitty {} does not exist", kitty_id))?;
+		let price = price_of(&*client, &at, kitty_id)?;
+
+		writeln!(output, "Kitty:      #{}", kitty_id)?;
+		write_kitty(&mut output, &kitty)?;
+		writeln!(output, "Owner:      {}", owner.to_ss58check())?;
+		match price {
+			Some(price) => writeln!(output, "Price:      {}", price)?,
+			None => writeln!(output, "Price:      not for sale")?,
+		}
+		writeln!(output, "Lineage:")?;
+		writeln!(output, "#{}", kitty_id)?;
+		write_lineage(&mut output, &*client, &at, kitty_id, "", self.depth)?;
+		writeln!(output)?;
+		write!(output, "{}", draw(&kitty, !self.no_colour))?;
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for InspectKittyCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Write the DNA, gender and traits of `kitty`.
+fn write_kitty(output: &mut dyn Write, kitty: &Kitty) -> io::Result<()> {
+	let traits = kitty.traits();
+
+	writeln!(output, "DNA:        0x{}", HexDisplay::from(&kitty.dna()))?;
+	writeln!(output, "Gender:     {}", kitty.gender().name())?;
+	writeln!(output, "Body:       {}", traits.body_colour_name())?;
+	writeln!(output, "Pattern:    {}", traits.pattern_name())?;
+	writeln!(output, "Eyes:       {}", traits.eye_colour_name())
+}
+
+/// Write the ancestors of `kitty_id` as a tree, down to `depth` generations.
+fn write_lineage<BE, C>(
+	output: &mut dyn Write,
+	client: &C,
+	at: &BlockId<Block>,
+	kitty_id: KittyIndex,
+	indent: &str,
+	depth: u32,
+) -> sc_cli::Result<()>
+where
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE>,
+{
+	if depth == 0 {
+		return Ok(())
+	}
+
+	let parents: Option<(KittyIndex, KittyIndex)> =
+		storage_value(client, at, Parents::<Runtime>::hashed_key_for(kitty_id))?;
+	let (parent1, parent2) = match parents {
+		Some(parents) => parents,
+		None => {
+			writeln!(output, "{}└── no recorded parents", indent)?;
+			return Ok(())
+		},
+	};
+
+	writeln!(output, "{}├── #{}", indent, parent1)?;
+	write_lineage(output, client, at, parent1, &format!("{}│   ", indent), depth - 1)?;
+	writeln!(output, "{}└── #{}", indent, parent2)?;
+	write_lineage(output, client, at, parent2, &format!("{}    ", indent), depth - 1)
+}
+
+/// The picture of a kitty, `B` marking its coat and `E` its eyes.
+const PICTURE: [&str; 7] = [
+	r"   /\_____/\",
+	r"  /  E   E  \",
+	r" (  ==  ^  == )",
+	r"  )  BBBBBBB  (",
+	r" (  BBBBBBBBB  )",
+	r" ( BBBBBBBBBBB )",
+	r"  (__)BBBBB(__)",
+];
+
+/// The 256-colour terminal codes of `pallet_kitties::BODY_COLOURS`.
+const BODY_COLOUR_CODES: [u8; 8] = [236, 255, 208, 245, 230, 130, 183, 94];
+
+/// The 256-colour terminal codes of `pallet_kitties::EYE_COLOURS`.
+const EYE_COLOUR_CODES: [u8; 4] = [40, 33, 214, 166];
+
+/// The white and ginger patches of calico coats.
+const CALICO_CODES: [u8; 2] = [255, 208];
+
+/// Draw `kitty` in ASCII art, with terminal colours if `colour` is set.
+fn draw(kitty: &Kitty, colour: bool) -> String {
+	let traits = kitty.traits();
+	let body_code = BODY_COLOUR_CODES[traits.body_colour as usize];
+	let paint = |text: char, code: u8| {
+		if colour {
+			format!("\x1b[38;5;{}m{}\x1b[0m", code, text)
+		} else {
+			text.to_string()
+		}
+	};
+
+	let mut picture = String::new();
+	for (y, line) in PICTURE.iter().enumerate() {
+		for (x, c) in line.chars().enumerate() {
+			match c {
+				'E' => picture.push_str(&paint('o', EYE_COLOUR_CODES[traits.eye_colour as usize])),
+				'B' => {
+					let (fill, code) = match traits.pattern_name() {
+						"tabby" if y % 2 == 0 => ('=', body_code),
+						"spotted" if (x + y) % 3 == 0 => ('o', body_code),
+						"calico" => match (x / 3 + y) % 3 {
+							0 => ('#', body_code),
+							patch => ('%', CALICO_CODES[patch - 1]),
+						},
+						_ => ('#', body_code),
+					};
+					picture.push_str(&paint(fill, code));
+				},
+				' ' => picture.push(' '),
+				c => picture.push_str(&paint(c, body_code)),
+			}
+		}
+		picture.push('\n');
+	}
+
+	picture
+}
+
+fn write_csv(output: &mut dyn Write, kitties: &[ExportedKitty]) -> io::Result<()> {
+	writeln!(output, "id,owner,dna,gender,body_colour,pattern,eye_colour,price")?;
+	for kitty in kitties {
+		writeln!(
+			output,
+			"{},{},{},{},{},{},{},{}",
+			kitty.id,
+			kitty.owner,
+			kitty.dna,
+			kitty.gender,
+			kitty.body_colour,
+			kitty.pattern,
+			kitty.eye_colour,
+			kitty.price.as_deref().unwrap_or_default(),
+		)?;
+	}
+
+	Ok(())
+}
+
+/// The block `at`, or the best block.
+fn block_id<C: HeaderBackend<Block>>(
+	client: &C,
+	at: Option<&BlockNumberOrHash>,
+) -> sc_cli::Result<BlockId<Block>> {
+	Ok(match at {
+		Some(at) => at.parse::<Block>()?,
+		None => BlockId::Hash(client.info().best_hash),
+	})
+}
+
+/// Every kitty with its owner, in the order of the storage keys.
+fn all_kitties<BE, C>(
+	client: &C,
+	at: &BlockId<Block>,
+) -> sc_cli::Result<Vec<(AccountId, KittyIndex, Kitty)>>
+where
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE>,
+{
+	let prefix = StorageKey(Kitties::<Runtime>::final_prefix().to_vec());
+
+	client
+		.storage_pairs(at, &prefix)?
+		.into_iter()
+		.map(|(key, value)| -> sc_cli::Result<_> {
+			let (owner, kitty_id) = decode_kitty_key(&key.0[prefix.0.len()..])
+				.ok_or_else(|| format!("Invalid kitty key 0x{}", HexDisplay::from(&key.0)))?;
+			let kitty = Kitty::decode(&mut &value.0[..])
+				.map_err(|e| format!("Invalid kitty {}: {}", kitty_id, e))?;
+			Ok((owner, kitty_id, kitty))
+		})
+		.collect()
+}
+
+/// Decode the `Blake2_128Concat` owner and kitty id of a key of `Kitties`, without its prefix.
+fn decode_kitty_key(key: &[u8]) -> Option<(AccountId, KittyIndex)> {
+	let mut input = key.get(16..)?;
+	let owner = AccountId::decode(&mut input).ok()?;
+	let mut input = input.get(16..)?;
+	let kitty_id = KittyIndex::decode(&mut input).ok()?;
+
+	Some((owner, kitty_id))
+}
+
+fn price_of<BE, C>(
+	client: &C,
+	at: &BlockId<Block>,
+	kitty_id: KittyIndex,
+) -> sc_cli::Result<Option<Balance>>
+where
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE>,
+{
+	storage_value(client, at, KittyPrices::<Runtime>::hashed_key_for(kitty_id))
+}
+
+/// Read and decode the value at `key`.
+fn storage_value<BE, C, V: Decode>(
+	client: &C,
+	at: &BlockId<Block>,
+	key: Vec<u8>,
+) -> sc_cli::Result<Option<V>>
+where
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE>,
+{
+	let key = StorageKey(key);
+
+	client
+		.storage(at, &key)?
+		.map(|value| V::decode(&mut &value.0[..]))
+		.transpose()
+		.map_err(|e| format!("Invalid value at 0x{}: {}", HexDisplay::from(&key.0), e).into())
+}
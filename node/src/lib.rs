@@ -1,3 +1,5 @@
 pub mod chain_spec;
+pub mod indexer;
+pub mod metrics;
 pub mod rpc;
 pub mod service;
@@ -6,6 +6,9 @@ mod chain_spec;
 mod service;
 mod cli;
 mod command;
+mod indexer;
+mod kitties;
+mod metrics;
 mod rpc;
 
 fn main() -> sc_cli::Result<()> {
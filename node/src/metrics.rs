@@ -0,0 +1,102 @@
+//! Prometheus metrics of the kitty marketplace, counted from the kitty events of each new best
+//! block and exported next to the standard Substrate metrics.
+
+use futures::{future, StreamExt};
+use node_template_runtime::{opaque::Block, Runtime};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use std::sync::Arc;
+use substrate_prometheus_endpoint::{register, Counter, PrometheusError, Registry, F64, U64};
+
+type KittyEvent = pallet_kitties::Event<Runtime>;
+
+/// The counters of the kitty marketplace.
+pub struct KittyMetrics {
+	minted: Counter<U64>,
+	bred: Counter<U64>,
+	transferred: Counter<U64>,
+	sold: Counter<U64>,
+	sale_volume: Counter<F64>,
+}
+
+impl KittyMetrics {
+	/// Register the counters in `registry`.
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			minted: register(
+				Counter::new(
+					"kitties_minted_total",
+					"Number of generation-0 kitties minted or claimed",
+				)?,
+				registry,
+			)?,
+			bred: register(
+				Counter::new(
+					"kitties_bred_total",
+					"Number of kitties hatched from a breeding pair",
+				)?,
+				registry,
+			)?,
+			transferred: register(
+				Counter::new(
+					"kitties_transferred_total",
+					"Number of kitties transferred or force transferred, sales excluded",
+				)?,
+				registry,
+			)?,
+			sold: register(
+				Counter::new("kitties_sold_total", "Number of kitties sold")?,
+				registry,
+			)?,
+			sale_volume: register(
+				Counter::new(
+					"kitties_sale_volume_total",
+					"Sum of the prices of the kitties sold, in the smallest unit of the balance",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Count `event`. The events come from `indexer::kitty_events`, which leaves out the
+	/// `KittyTransferred` of a sale, so sales are only counted in `kitties_sold_total`.
+	fn observe(&self, event: &KittyEvent) {
+		match event {
+			KittyEvent::KittyCreated(..) | KittyEvent::KittyClaimed(..) => self.minted.inc(),
+			KittyEvent::KittyCreatedByBreeding(..) => self.bred.inc(),
+			KittyEvent::KittyTransferred(..) | KittyEvent::KittyForceTransferred(..) =>
+				self.transferred.inc(),
+			KittyEvent::KittySold(_, _, _, price) => {
+				self.sold.inc();
+				self.sale_volume.inc_by(*price as f64);
+			},
+			_ => {},
+		}
+	}
+}
+
+/// Count the kitty events of each new best block of `client` in `metrics`.
+///
+/// The blocks of a retracted fork stay counted, so the counters may run slightly ahead of the
+/// canonical chain after a reorg.
+pub async fn run<BE, C>(client: Arc<C>, metrics: KittyMetrics)
+where
+	BE: Backend<Block>,
+	C: BlockchainEvents<Block> + StorageProvider<Block, BE>,
+{
+	client
+		.import_notification_stream()
+		.filter(|notification| future::ready(notification.is_new_best))
+		.for_each(|notification| {
+			match crate::indexer::kitty_events(&*client, notification.hash) {
+				Ok(events) => events.iter().for_each(|(_, event)| metrics.observe(event)),
+				Err(e) => log::warn!(
+					target: "kitties-metrics",
+					"Unable to read the kitty events of {}: {}",
+					notification.hash,
+					e
+				),
+			}
+			future::ready(())
+		})
+		.await
+}
@@ -5,46 +5,201 @@
 
 #![warn(missing_docs)]
 
-use std::sync::Arc;
+mod kitties;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::indexer::KittyIndexDb;
+use fc_rpc::{
+	EthBlockDataCache, OverrideHandle, RuntimeApiStorageOverride, SchemaV1Override,
+	SchemaV2Override, SchemaV3Override, StorageOverride,
+};
+use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
+use fp_storage::EthereumStorageSchema;
+use futures::channel::mpsc::Sender;
+use jsonrpc_pubsub::manager::SubscriptionManager;
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Index};
+use sc_client_api::{
+	backend::{AuxStore, StateBackend},
+	Backend, BlockchainEvents, StorageProvider,
+};
+use sc_consensus_manual_seal::EngineCommand;
+use sc_network::NetworkService;
+use sc_rpc::SubscriptionTaskExecutor;
 pub use sc_rpc_api::DenyUnsafe;
+use sc_transaction_pool::{ChainApi, Pool};
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
 use sp_blockchain::{Error as BlockChainError, HeaderBackend, HeaderMetadata};
+use sp_runtime::traits::BlakeTwo256;
+
+/// The most filters `eth_newFilter` keeps at a time.
+const MAX_STORED_FILTERS: usize = 500;
+
+/// The most logs one `eth_getLogs` call returns.
+const MAX_PAST_LOGS: u32 = 10_000;
+
+/// How many blocks the eth RPC keeps the fee history of.
+pub const FEE_HISTORY_LIMIT: u64 = 2048;
 
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, A: ChainApi> {
 	/// The client instance to use.
 	pub client: Arc<C>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
+	/// Graph pool instance.
+	pub graph: Arc<Pool<A>>,
+	/// Whether the node is an authority.
+	pub is_authority: bool,
+	/// Network service.
+	pub network: Arc<NetworkService<Block, Hash>>,
+	/// The mapping of Ethereum blocks and transactions to Substrate ones.
+	pub frontier_backend: Arc<fc_db::Backend<Block>>,
+	/// The filters installed with `eth_newFilter`.
+	pub filter_pool: FilterPool,
+	/// The fee history of the recent blocks.
+	pub fee_history_cache: FeeHistoryCache,
+	/// Reads the Ethereum blocks and receipts from the runtime storage.
+	pub overrides: Arc<OverrideHandle<Block>>,
+	/// Caches the Ethereum blocks and statuses read through `overrides`.
+	pub block_data_cache: Arc<EthBlockDataCache<Block>>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Sink for `engine_createBlock` commands, when blocks are sealed manually.
+	pub command_sink: Option<Sender<EngineCommand<Hash>>>,
+	/// The kitty index, when the node keeps one.
+	pub kitty_index: Option<Arc<KittyIndexDb>>,
+}
+
+/// The readers of the Ethereum data each `pallet_ethereum` storage schema keeps.
+pub fn overrides_handle<C, BE>(client: Arc<C>) -> Arc<OverrideHandle<Block>>
+where
+	BE: Backend<Block> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + AuxStore,
+	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError>,
+	C: Send + Sync + 'static,
+	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+{
+	let mut schemas: BTreeMap<_, Box<dyn StorageOverride<_> + Send + Sync>> = BTreeMap::new();
+	schemas.insert(EthereumStorageSchema::V1, Box::new(SchemaV1Override::new(client.clone())));
+	schemas.insert(EthereumStorageSchema::V2, Box::new(SchemaV2Override::new(client.clone())));
+	schemas.insert(EthereumStorageSchema::V3, Box::new(SchemaV3Override::new(client.clone())));
+
+	Arc::new(OverrideHandle { schemas, fallback: Box::new(RuntimeApiStorageOverride::new(client)) })
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(deps: FullDeps<C, P>) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
+pub fn create_full<C, P, BE, A>(
+	deps: FullDeps<C, P, A>,
+	subscription_executor: SubscriptionTaskExecutor,
+) -> jsonrpc_core::IoHandler<sc_rpc::Metadata>
 where
-	C: ProvideRuntimeApi<Block>,
+	BE: Backend<Block> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	C: ProvideRuntimeApi<Block> + AuxStore,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error = BlockChainError> + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, BE>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	C::Api: pallet_kitties_runtime_api::KittiesApi<Block, Balance, u32>,
+	C::Api: fp_rpc::ConvertTransactionRuntimeApi<Block>,
+	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
 	C::Api: BlockBuilder<Block>,
-	P: TransactionPool + 'static,
+	P: TransactionPool<Block = Block> + 'static,
+	A: ChainApi<Block = Block> + 'static,
 {
+	use fc_rpc::{
+		EthApi, EthApiServer, EthFilterApi, EthFilterApiServer, EthPubSubApi, EthPubSubApiServer,
+		HexEncodedIdProvider, NetApi, NetApiServer, Web3Api, Web3ApiServer,
+	};
+	use kitties::{Kitties, KittiesApi};
+	use pallet_contracts_rpc::{Contracts, ContractsApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
-	let FullDeps { client, pool, deny_unsafe } = deps;
+	let FullDeps {
+		client,
+		pool,
+		graph,
+		is_authority,
+		network,
+		frontier_backend,
+		filter_pool,
+		fee_history_cache,
+		overrides,
+		block_data_cache,
+		deny_unsafe,
+		command_sink,
+		kitty_index,
+	} = deps;
 
-	io.extend_with(SystemApi::to_delegate(FullSystem::new(client.clone(), pool, deny_unsafe)));
+	io.extend_with(SystemApi::to_delegate(FullSystem::new(
+		client.clone(),
+		pool.clone(),
+		deny_unsafe,
+	)));
 
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone())));
 
+	io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
+
+	if let Some(command_sink) = command_sink {
+		io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+	}
+
+	io.extend_with(KittiesApi::to_delegate(Kitties::new(
+		client.clone(),
+		kitty_index,
+		SubscriptionManager::new(Arc::new(subscription_executor.clone())),
+	)));
+
+	io.extend_with(EthApiServer::to_delegate(EthApi::new(
+		client.clone(),
+		pool.clone(),
+		graph,
+		Some(node_template_runtime::TransactionConverter),
+		network.clone(),
+		// the node holds no Ethereum keys, transactions come signed by the wallets
+		Vec::new(),
+		overrides.clone(),
+		frontier_backend.clone(),
+		is_authority,
+		block_data_cache.clone(),
+		FEE_HISTORY_LIMIT,
+		fee_history_cache,
+	)));
+
+	io.extend_with(EthFilterApiServer::to_delegate(EthFilterApi::new(
+		client.clone(),
+		frontier_backend,
+		filter_pool,
+		MAX_STORED_FILTERS,
+		MAX_PAST_LOGS,
+		block_data_cache,
+	)));
+
+	io.extend_with(NetApiServer::to_delegate(NetApi::new(client.clone(), network.clone(), true)));
+
+	io.extend_with(Web3ApiServer::to_delegate(Web3Api::new(client.clone())));
+
+	io.extend_with(EthPubSubApiServer::to_delegate(EthPubSubApi::new(
+		pool,
+		client.clone(),
+		network,
+		SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
+			HexEncodedIdProvider::default(),
+			Arc::new(subscription_executor),
+		),
+		overrides,
+	)));
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
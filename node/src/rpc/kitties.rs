@@ -0,0 +1,499 @@
+//! The `kitties_*` RPC methods, which serve the kitty index of the node, push the kitty events
+//! of new blocks to subscribers and preview the offspring of breeding pairs.
+
+use crate::indexer::{self, KittyHistory, KittyIndexDb};
+use futures::{future, stream, FutureExt, SinkExt, StreamExt};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use node_template_runtime::{opaque::Block, AccountId, Balance, BlockNumber, Hash, Runtime};
+use pallet_kitties::{
+	CommitAction, Kitty, ParamChange, ScheduledAction, BODY_COLOURS, EYE_COLOURS, PATTERNS,
+};
+use pallet_kitties_runtime_api::{BreedingPreview, KittiesApi as KittiesRuntimeApi, KittyOrDna};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hexdisplay::HexDisplay, Bytes};
+use sp_runtime::{generic::BlockId, traits::Header as _, PerThing, Perbill};
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+
+/// The kitty index of the runtime.
+type KittyIndex = u32;
+
+type KittyEvent = pallet_kitties::Event<Runtime>;
+
+/// The kitties RPC methods.
+#[rpc]
+pub trait KittiesApi {
+	/// RPC metadata
+	type Metadata;
+
+	/// The ownership and sale history of a kitty, oldest first.
+	#[rpc(name = "kitties_history")]
+	fn history(&self, kitty_id: KittyIndex) -> Result<KittyHistory>;
+
+	/// The probability of each gender and trait of the offspring of two kitties, each given by
+	/// id or by DNA, or `None` if a kitty id does not exist.
+	#[rpc(name = "kitties_previewBreeding")]
+	fn preview_breeding(
+		&self,
+		kitty1: KittyIdOrDna,
+		kitty2: KittyIdOrDna,
+		at: Option<Hash>,
+	) -> Result<Option<BreedingPreviewResponse>>;
+
+	/// Push the kitty events of each new best block, or of each finalized block, that match
+	/// `filter`.
+	#[pubsub(subscription = "kitties_events", subscribe, name = "kitties_subscribeEvents")]
+	fn subscribe_events(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<KittyEventNotification>,
+		filter: Option<EventFilter>,
+	);
+
+	/// Stop pushing kitty events.
+	#[pubsub(subscription = "kitties_events", unsubscribe, name = "kitties_unsubscribeEvents")]
+	fn unsubscribe_events(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool>;
+}
+
+/// A kitty id, or a DNA as 0x-prefixed hex for a kitty that does not exist.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum KittyIdOrDna {
+	/// The id of an existing kitty.
+	Id(KittyIndex),
+	/// The DNA of a kitty.
+	Dna(Bytes),
+}
+
+impl KittyIdOrDna {
+	fn into_kitty(self) -> Result<KittyOrDna<KittyIndex>> {
+		match self {
+			KittyIdOrDna::Id(kitty_id) => Ok(KittyOrDna::Id(kitty_id)),
+			KittyIdOrDna::Dna(dna) => dna
+				.0
+				.as_slice()
+				.try_into()
+				.map(KittyOrDna::Dna)
+				.map_err(|_| RpcError::invalid_params("A kitty DNA is 16 bytes long.")),
+		}
+	}
+}
+
+/// The probability of each gender and trait of the offspring of two kitties, by name.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreedingPreviewResponse {
+	/// The probability of a female and of a male offspring.
+	pub gender: BTreeMap<&'static str, f64>,
+	/// The probability of each body colour.
+	pub body_colour: BTreeMap<&'static str, f64>,
+	/// The probability of each coat pattern.
+	pub pattern: BTreeMap<&'static str, f64>,
+	/// The probability of each eye colour.
+	pub eye_colour: BTreeMap<&'static str, f64>,
+}
+
+impl From<BreedingPreview> for BreedingPreviewResponse {
+	fn from(preview: BreedingPreview) -> Self {
+		fn by_name(
+			names: &[&'static str],
+			probabilities: &[Perbill],
+		) -> BTreeMap<&'static str, f64> {
+			names
+				.iter()
+				.zip(probabilities)
+				.map(|(name, probability)| {
+					(*name, f64::from(probability.deconstruct()) / f64::from(Perbill::ACCURACY))
+				})
+				.collect()
+		}
+
+		Self {
+			gender: by_name(&["female", "male"], &preview.gender),
+			body_colour: by_name(&BODY_COLOURS, &preview.body_colour),
+			pattern: by_name(&PATTERNS, &preview.pattern),
+			eye_colour: by_name(&EYE_COLOURS, &preview.eye_colour),
+		}
+	}
+}
+
+/// The kitty events a subscriber is interested in. Every given criterion must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventFilter {
+	/// Only the events about this kitty.
+	pub kitty_id: Option<KittyIndex>,
+	/// Only the events involving this account.
+	pub account: Option<AccountId>,
+	/// Only the events of these types, such as `KittySold`.
+	pub event_types: Option<Vec<String>>,
+	/// Follow the finalized blocks rather than the best blocks.
+	#[serde(default)]
+	pub finalized: bool,
+}
+
+/// A kitty event pushed to a subscriber.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KittyEventNotification {
+	/// The number of the block of the event.
+	pub block_number: BlockNumber,
+	/// The hash of the block of the event.
+	pub block_hash: Hash,
+	/// The index of the event among the events of the block.
+	pub event_index: u32,
+	/// The type of the event, such as `KittySold`.
+	pub event_type: String,
+	/// The kitties the event is about.
+	pub kitty_ids: Vec<KittyIndex>,
+	/// The accounts the event involves.
+	pub accounts: Vec<AccountId>,
+	/// The fields of the event. Balances are decimal strings, as they may not fit in a JSON
+	/// number.
+	pub data: Value,
+}
+
+/// The kitties RPC methods.
+pub struct Kitties<C, BE> {
+	client: Arc<C>,
+	index: Option<Arc<KittyIndexDb>>,
+	manager: SubscriptionManager,
+	_backend: PhantomData<BE>,
+}
+
+impl<C, BE> Kitties<C, BE> {
+	/// Push the kitty events of `client`, and serve the history of `index` if the node keeps one.
+	pub fn new(
+		client: Arc<C>,
+		index: Option<Arc<KittyIndexDb>>,
+		manager: SubscriptionManager,
+	) -> Self {
+		Self { client, index, manager, _backend: PhantomData }
+	}
+}
+
+impl<C, BE> KittiesApi for Kitties<C, BE>
+where
+	BE: Backend<Block> + 'static,
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, BE>,
+	C: ProvideRuntimeApi<Block> + Send + Sync + 'static,
+	C::Api: KittiesRuntimeApi<Block, Balance, KittyIndex>,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn history(&self, kitty_id: KittyIndex) -> Result<KittyHistory> {
+		let index = self.index.as_ref().ok_or_else(|| RpcError {
+			code: ErrorCode::ServerError(1),
+			message: "The node keeps no kitty index, start it with `--kitty-index`.".into(),
+			data: None,
+		})?;
+
+		index.history(kitty_id).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(2),
+			message: "Unable to read the kitty index.".into(),
+			data: Some(e.to_string().into()),
+		})
+	}
+
+	fn preview_breeding(
+		&self,
+		kitty1: KittyIdOrDna,
+		kitty2: KittyIdOrDna,
+		at: Option<Hash>,
+	) -> Result<Option<BreedingPreviewResponse>> {
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let preview = self
+			.client
+			.runtime_api()
+			.preview_breeding(&at, kitty1.into_kitty()?, kitty2.into_kitty()?)
+			.map_err(|e| RpcError {
+				code: ErrorCode::ServerError(3),
+				message: "Unable to preview the breeding.".into(),
+				data: Some(e.to_string().into()),
+			})?;
+
+		Ok(preview.map(Into::into))
+	}
+
+	fn subscribe_events(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<KittyEventNotification>,
+		filter: Option<EventFilter>,
+	) {
+		let filter = filter.unwrap_or_default();
+		let client = self.client.clone();
+
+		let blocks = if filter.finalized {
+			// a finality notification may finalize several blocks at once
+			let mut last = None;
+			let client = client.clone();
+			client
+				.finality_notification_stream()
+				.flat_map(move |notification| {
+					let number = *notification.header.number();
+					let blocks = last.replace(number).map_or_else(Vec::new, |last| {
+						ancestors(&*client, &notification.header, last)
+					});
+					stream::iter(blocks.into_iter().rev().chain([(number, notification.hash)]))
+				})
+				.boxed()
+		} else {
+			client
+				.import_notification_stream()
+				.filter(|notification| future::ready(notification.is_new_best))
+				.map(|notification| (*notification.header.number(), notification.hash))
+				.boxed()
+		};
+
+		let notifications = blocks.flat_map(move |(number, hash)| {
+			let events = indexer::kitty_events(&*client, hash).unwrap_or_else(|e| {
+				log::warn!(target: "rpc", "Unable to read the kitty events of {}: {}", hash, e);
+				Vec::new()
+			});
+			stream::iter(
+				events
+					.into_iter()
+					.map(|(event_index, event)| describe(number, hash, event_index, event))
+					.filter(|notification| filter.matches(notification))
+					.map(|notification| Ok(Ok(notification)))
+					.collect::<Vec<_>>(),
+			)
+		});
+
+		self.manager.add(subscriber, |sink| {
+			notifications
+				.forward(sink.sink_map_err(
+					|e| log::warn!(target: "rpc", "Error sending kitty events: {:?}", e),
+				))
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_events(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
+
+impl EventFilter {
+	fn matches(&self, notification: &KittyEventNotification) -> bool {
+		self.kitty_id
+			.map_or(true, |kitty_id| notification.kitty_ids.contains(&kitty_id)) &&
+			self.account
+				.as_ref()
+				.map_or(true, |account| notification.accounts.contains(account)) &&
+			self.event_types
+				.as_ref()
+				.map_or(true, |types| types.contains(&notification.event_type))
+	}
+}
+
+/// The blocks between the block numbered `last` and `header`, both excluded, newest first.
+fn ancestors<C: HeaderBackend<Block>>(
+	client: &C,
+	header: &<Block as sp_runtime::traits::Block>::Header,
+	last: BlockNumber,
+) -> Vec<(BlockNumber, Hash)> {
+	let mut blocks = Vec::new();
+	let mut parent_hash = *header.parent_hash();
+	while let Ok(Some(parent)) = client.header(BlockId::Hash(parent_hash)) {
+		if *parent.number() <= last {
+			break
+		}
+		blocks.push((*parent.number(), parent_hash));
+		parent_hash = *parent.parent_hash();
+	}
+	blocks
+}
+
+/// The type, kitties, accounts and fields of `event`.
+///
+/// Matches every event without a wildcard, so a new pallet event doesn't build until it is
+/// described here.
+fn describe(
+	block_number: BlockNumber,
+	block_hash: Hash,
+	event_index: u32,
+	event: KittyEvent,
+) -> KittyEventNotification {
+	let dna = |kitty: &Kitty| format!("0x{}", HexDisplay::from(&kitty.0));
+
+	let (event_type, kitty_ids, accounts, data) = match event {
+		KittyEvent::KittyCreated(owner, kitty_id, kitty) => (
+			"KittyCreated",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({ "owner": owner, "kittyId": kitty_id, "dna": dna(&kitty) }),
+		),
+		KittyEvent::KittyCreatedByBreeding(owner, kitty_id, kitty) => (
+			"KittyCreatedByBreeding",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({ "owner": owner, "kittyId": kitty_id, "dna": dna(&kitty) }),
+		),
+		KittyEvent::EggLaid(owner, kitty_id, hatch_at) => (
+			"EggLaid",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({ "owner": owner, "kittyId": kitty_id, "hatchAt": hatch_at }),
+		),
+		KittyEvent::KittyTransferred(from, to, kitty_id) => (
+			"KittyTransferred",
+			vec![kitty_id],
+			vec![from.clone(), to.clone()],
+			json!({ "from": from, "to": to, "kittyId": kitty_id }),
+		),
+		KittyEvent::KittyApproved(owner, approved, kitty_id) => (
+			"KittyApproved",
+			vec![kitty_id],
+			[Some(owner.clone()), approved.clone()].into_iter().flatten().collect(),
+			json!({ "owner": owner, "approved": approved, "kittyId": kitty_id }),
+		),
+		KittyEvent::KittyPriceUpdated(owner, kitty_id, price) => (
+			"KittyPriceUpdated",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({
+				"owner": owner,
+				"kittyId": kitty_id,
+				"price": price.map(|price| price.to_string()),
+			}),
+		),
+		KittyEvent::KittySold(seller, buyer, kitty_id, price) => (
+			"KittySold",
+			vec![kitty_id],
+			vec![seller.clone(), buyer.clone()],
+			json!({
+				"seller": seller,
+				"buyer": buyer,
+				"kittyId": kitty_id,
+				"price": price.to_string(),
+			}),
+		),
+		KittyEvent::KittyForceTransferred(from, to, kitty_id) => (
+			"KittyForceTransferred",
+			vec![kitty_id],
+			vec![from.clone(), to.clone()],
+			json!({ "from": from, "to": to, "kittyId": kitty_id }),
+		),
+		KittyEvent::KittyForceDelisted(kitty_id) =>
+			("KittyForceDelisted", vec![kitty_id], vec![], json!({ "kittyId": kitty_id })),
+		KittyEvent::KittyForceBurned(owner, kitty_id) => (
+			"KittyForceBurned",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({ "owner": owner, "kittyId": kitty_id }),
+		),
+		KittyEvent::PauseFlagsUpdated(flags) => (
+			"PauseFlagsUpdated",
+			vec![],
+			vec![],
+			json!({
+				"minting": flags.minting,
+				"breeding": flags.breeding,
+				"trading": flags.trading,
+			}),
+		),
+		KittyEvent::PresaleScheduled(merkle_root, start, end) => (
+			"PresaleScheduled",
+			vec![],
+			vec![],
+			json!({
+				"merkleRoot": format!("0x{}", HexDisplay::from(&merkle_root)),
+				"start": start,
+				"end": end,
+			}),
+		),
+		KittyEvent::KittyClaimed(owner, kitty_id, kitty) => (
+			"KittyClaimed",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({ "owner": owner, "kittyId": kitty_id, "dna": dna(&kitty) }),
+		),
+		KittyEvent::OrdersCancelled(seller, nonce) => (
+			"OrdersCancelled",
+			vec![],
+			vec![seller.clone()],
+			json!({ "seller": seller, "newNonce": nonce }),
+		),
+		KittyEvent::DnaCommitted(who, action) => {
+			let (kitty_ids, action) = match action {
+				CommitAction::Create => (vec![], json!("create")),
+				CommitAction::Breed(kitty_1, kitty_2) =>
+					(vec![kitty_1, kitty_2], json!({ "breed": [kitty_1, kitty_2] })),
+			};
+			("DnaCommitted", kitty_ids, vec![who.clone()], json!({ "who": who, "action": action }))
+		},
+		KittyEvent::CommitmentReaped(who, deposit) => (
+			"CommitmentReaped",
+			vec![],
+			vec![who.clone()],
+			json!({ "who": who, "deposit": deposit.to_string() }),
+		),
+		KittyEvent::MarketParamChanged(change) => (
+			"MarketParamChanged",
+			vec![],
+			vec![],
+			match change {
+				ParamChange::SaleFee(fee) => json!({ "saleFee": fee.deconstruct() }),
+				ParamChange::BreedingCooldown(blocks) => json!({ "breedingCooldown": blocks }),
+			},
+		),
+		KittyEvent::ActionScheduled(owner, kitty_id, action, when) => {
+			let (accounts, action) = match action {
+				ScheduledAction::Transfer(to) =>
+					(vec![owner.clone(), to.clone()], json!({ "transfer": to })),
+				ScheduledAction::SetPrice(price) => (
+					vec![owner.clone()],
+					json!({ "setPrice": price.map(|price| price.to_string()) }),
+				),
+			};
+			(
+				"ActionScheduled",
+				vec![kitty_id],
+				accounts,
+				json!({ "owner": owner, "kittyId": kitty_id, "action": action, "when": when }),
+			)
+		},
+		KittyEvent::ActionCancelled(owner, kitty_id) => (
+			"ActionCancelled",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({ "owner": owner, "kittyId": kitty_id }),
+		),
+		KittyEvent::ActionRun(owner, kitty_id, result) => (
+			"ActionRun",
+			vec![kitty_id],
+			vec![owner.clone()],
+			json!({
+				"owner": owner,
+				"kittyId": kitty_id,
+				"error": result.err().map(|error| format!("{:?}", error)),
+			}),
+		),
+		KittyEvent::__Ignore(..) => unreachable!("`__Ignore` is never emitted; qed"),
+	};
+
+	KittyEventNotification {
+		block_number,
+		block_hash,
+		event_index,
+		event_type: event_type.into(),
+		kitty_ids,
+		accounts,
+		data,
+	}
+}
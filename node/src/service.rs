@@ -1,16 +1,43 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::{BlockBackend, ExecutorProvider};
+use crate::{indexer::KittyIndexDb, metrics::KittyMetrics};
+use fc_mapping_sync::{MappingSyncWorker, SyncStrategy};
+use fc_rpc::EthTask;
+use fc_rpc_core::types::{FeeHistoryCache, FilterPool};
+use futures::{channel::mpsc::Receiver, future, stream, Stream, StreamExt};
+use futures_timer::Delay;
+use node_template_runtime::{self, opaque::Block, Hash, RuntimeApi};
+use remote_signer::RemoteKeystore;
+use sc_client_api::{BlockBackend, BlockchainEvents, ExecutorProvider};
 use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::aura::AuraConsensusDataProvider, EngineCommand, ManualSealParams,
+};
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
-use sc_keystore::LocalKeystore;
-use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
+use sc_service::{
+	config::DatabaseSource, error::Error as ServiceError, BasePath, ChainType, Configuration,
+	TaskManager,
+};
 use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_transaction_pool_api::TransactionPool;
 use sp_consensus::SlotData;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use std::{
+	collections::BTreeMap,
+	path::PathBuf,
+	pin::Pin,
+	str::FromStr,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use substrate_prometheus_endpoint::Registry;
+
+/// How many blocks an `eth_newFilter` filter lives without being polled.
+const FILTER_RETAIN_THRESHOLD: u64 = 100;
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -37,8 +64,38 @@ type FullClient =
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
 
+/// How blocks are sealed when the node runs with manual seal instead of Aura and GRANDPA.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sealing {
+	/// Seal a block as soon as a transaction enters the pool.
+	Instant,
+	/// Seal a block on each `engine_createBlock` call.
+	Manual,
+	/// Seal a block every given number of milliseconds.
+	Interval(u64),
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Sealing::Instant),
+			"manual" => Ok(Sealing::Manual),
+			millis => match millis.parse() {
+				Ok(0) | Err(_) => Err(format!(
+					"expected `instant`, `manual` or a number of milliseconds, got `{}`",
+					s
+				)),
+				Ok(millis) => Ok(Sealing::Interval(millis)),
+			},
+		}
+	}
+}
+
 pub fn new_partial(
 	config: &Configuration,
+	sealing: Option<Sealing>,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
@@ -59,8 +116,11 @@ pub fn new_partial(
 	>,
 	ServiceError,
 > {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other("Remote Keystores are not supported.".into()))
+	// manual seal trusts whoever reaches the node, so it is kept off shared chains
+	if sealing.is_some() && config.chain_spec.chain_type() != ChainType::Development {
+		return Err(ServiceError::Other(
+			"--sealing is only allowed on a development chain, such as --dev".into(),
+		))
 	}
 
 	let telemetry = config
@@ -113,7 +173,13 @@ pub fn new_partial(
 
 	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
 
-	let import_queue =
+	let import_queue = if sealing.is_some() {
+		sc_consensus_manual_seal::import_queue(
+			Box::new(client.clone()),
+			&task_manager.spawn_essential_handle(),
+			config.prometheus_registry(),
+		)
+	} else {
 		sc_consensus_aura::import_queue::<AuraPair, _, _, _, _, _, _>(ImportQueueParams {
 			block_import: grandpa_block_import.clone(),
 			justification_import: Some(Box::new(grandpa_block_import.clone())),
@@ -136,7 +202,8 @@ pub fn new_partial(
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
-		})?;
+		})?
+	};
 
 	Ok(sc_service::PartialComponents {
 		client,
@@ -150,15 +217,58 @@ pub fn new_partial(
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
+/// The directory of the database mapping Ethereum blocks and transactions to Substrate ones.
+fn frontier_database_dir(config: &Configuration) -> PathBuf {
+	config
+		.base_path
+		.as_ref()
+		.map(|base_path| base_path.config_dir(config.chain_spec.id()))
+		.unwrap_or_else(|| {
+			BasePath::from_project("", "", "node-template").config_dir(config.chain_spec.id())
+		})
+		.join("frontier")
+		.join("db")
+}
+
+/// Opens the database mapping Ethereum blocks and transactions to Substrate ones.
+fn open_frontier_backend(config: &Configuration) -> Result<Arc<fc_db::Backend<Block>>, String> {
+	Ok(Arc::new(fc_db::Backend::<Block>::new(&fc_db::DatabaseSettings {
+		source: DatabaseSource::RocksDb { path: frontier_database_dir(config), cache_size: 0 },
+	})?))
+}
+
+/// The keystore of the remote signer at `url`, which accepts the token in `token_file`, see
+/// `remote-signer`.
+fn remote_keystore(
+	url: &String,
+	token_file: Option<&PathBuf>,
+) -> Result<Arc<RemoteKeystore>, String> {
+	if !url.starts_with("http://") && !url.starts_with("https://") {
+		return Err("the signer URL must be an http:// or https:// URL".into())
+	}
+	let token_file = token_file.ok_or("the signer needs a --keystore-token-file")?;
+	let token = remote_signer::read_token(token_file)?;
+
+	Ok(Arc::new(RemoteKeystore::new(url, &token)))
 }
 
-/// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds a new service for a full client, sealing blocks with `sealing` if given and indexing
+/// the kitty events in the SQLite database at `kitty_index` if given. A remote signer is sent the
+/// token in `keystore_token_file`.
+pub fn new_full(
+	mut config: Configuration,
+	sealing: Option<Sealing>,
+	kitty_index: Option<PathBuf>,
+	keystore_token_file: Option<PathBuf>,
+) -> Result<TaskManager, ServiceError> {
+	// the index reads the events of every block from its state, and would stall at pruned ones
+	if kitty_index.is_some() && !config.state_pruning.is_archive() {
+		return Err(ServiceError::Other(
+			"--kitty-index needs the state of every block, run the node with --pruning archive"
+				.into(),
+		))
+	}
+
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -168,10 +278,10 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
-	} = new_partial(&config)?;
+	} = new_partial(&config, sealing)?;
 
 	if let Some(url) = &config.keystore_remote {
-		match remote_keystore(url) {
+		match remote_keystore(url, keystore_token_file.as_ref()) {
 			Ok(k) => keystore_container.set_remote_keystore(k),
 			Err(e) =>
 				return Err(ServiceError::Other(format!(
@@ -222,15 +332,109 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	// in manual mode `engine_createBlock` calls are sent down this channel to the sealing task
+	let (command_sink, commands_stream) = match sealing {
+		Some(Sealing::Manual) => {
+			let (sink, stream) = futures::channel::mpsc::channel(1024);
+			(Some(sink), Some(stream))
+		},
+		_ => (None, None),
+	};
+
+	let kitty_index =
+		kitty_index
+			.map(|path| KittyIndexDb::open(&path).map(Arc::new))
+			.transpose()
+			.map_err(|e| ServiceError::Other(format!("Unable to open the kitty index: {}", e)))?;
+
+	if let Some(kitty_index) = &kitty_index {
+		// the index falls behind rather than failing, so it is not essential
+		task_manager.spawn_handle().spawn_blocking(
+			"kitties-indexer",
+			None,
+			crate::indexer::run(client.clone(), kitty_index.clone()),
+		);
+	}
+
+	if let Some(registry) = &prometheus_registry {
+		let metrics = KittyMetrics::register(registry)?;
+		task_manager.spawn_handle().spawn(
+			"kitties-metrics",
+			None,
+			crate::metrics::run(client.clone(), metrics),
+		);
+	}
+
+	let frontier_backend = open_frontier_backend(&config).map_err(|e| {
+		ServiceError::Other(format!("Unable to open the Ethereum mapping database: {}", e))
+	})?;
+	let filter_pool: FilterPool = Arc::new(Mutex::new(BTreeMap::new()));
+	let fee_history_cache: FeeHistoryCache = Arc::new(Mutex::new(BTreeMap::new()));
+	let overrides = crate::rpc::overrides_handle(client.clone());
+	let block_data_cache = Arc::new(fc_rpc::EthBlockDataCache::new(
+		task_manager.spawn_handle(),
+		overrides.clone(),
+		50,
+		50,
+	));
+
+	// the eth RPC finds Ethereum blocks and transactions through this mapping
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-mapping-sync-worker",
+		None,
+		MappingSyncWorker::new(
+			client.import_notification_stream(),
+			Duration::new(6, 0),
+			client.clone(),
+			backend.clone(),
+			frontier_backend.clone(),
+			SyncStrategy::Normal,
+		)
+		.for_each(|()| future::ready(())),
+	);
+
+	// drops the filters of `eth_newFilter` that were not polled for a while
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-filter-pool",
+		None,
+		EthTask::filter_pool_task(client.clone(), filter_pool.clone(), FILTER_RETAIN_THRESHOLD),
+	);
+
+	task_manager.spawn_essential_handle().spawn(
+		"frontier-fee-history",
+		None,
+		EthTask::fee_history_task(
+			client.clone(),
+			overrides.clone(),
+			fee_history_cache.clone(),
+			crate::rpc::FEE_HISTORY_LIMIT,
+		),
+	);
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
+		let network = network.clone();
+		let is_authority = role.is_authority();
 
-		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
-
-			Ok(crate::rpc::create_full(deps))
+		Box::new(move |deny_unsafe, subscription_executor| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				graph: pool.pool().clone(),
+				is_authority,
+				network: network.clone(),
+				frontier_backend: frontier_backend.clone(),
+				filter_pool: filter_pool.clone(),
+				fee_history_cache: fee_history_cache.clone(),
+				overrides: overrides.clone(),
+				block_data_cache: block_data_cache.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+				kitty_index: kitty_index.clone(),
+			};
+
+			Ok(crate::rpc::create_full(deps, subscription_executor))
 		})
 	};
 
@@ -247,6 +451,22 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(sealing) = sealing {
+		start_manual_seal(
+			sealing,
+			&task_manager,
+			client,
+			transaction_pool,
+			select_chain,
+			commands_stream,
+			prometheus_registry.as_ref(),
+			telemetry.as_ref(),
+		)?;
+
+		network_starter.start_network();
+		return Ok(task_manager)
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -345,3 +565,89 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	network_starter.start_network();
 	Ok(task_manager)
 }
+
+/// Seals blocks with manual seal in place of Aura, finalizing them in place of GRANDPA.
+#[allow(clippy::too_many_arguments)]
+fn start_manual_seal(
+	sealing: Sealing,
+	task_manager: &TaskManager,
+	client: Arc<FullClient>,
+	transaction_pool: Arc<sc_transaction_pool::FullPool<Block, FullClient>>,
+	select_chain: FullSelectChain,
+	commands_stream: Option<Receiver<EngineCommand<Hash>>>,
+	prometheus_registry: Option<&Registry>,
+	telemetry: Option<&Telemetry>,
+) -> Result<(), ServiceError> {
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry,
+		telemetry.map(|x| x.handle()),
+	);
+
+	let seal = |create_empty| EngineCommand::SealNewBlock {
+		create_empty,
+		finalize: true,
+		parent_hash: None,
+		sender: None,
+	};
+	let commands_stream: Pin<Box<dyn Stream<Item = EngineCommand<Hash>> + Send>> = match sealing {
+		Sealing::Instant =>
+			Box::pin(transaction_pool.import_notification_stream().map(move |_| seal(false))),
+		Sealing::Manual =>
+			Box::pin(commands_stream.expect("manual sealing always has a command stream; qed")),
+		Sealing::Interval(millis) => Box::pin(stream::unfold((), move |()| async move {
+			Delay::new(Duration::from_millis(millis)).await;
+			Some((seal(true), ()))
+		})),
+	};
+
+	// Aura still checks that every block is in a later slot than its parent, so blocks sealed
+	// faster than the slot duration get timestamps one slot apart rather than the wall clock.
+	let slot_duration = sc_consensus_aura::slot_duration(&*client)?.slot_duration();
+	let last_timestamp = Arc::new(AtomicU64::new(0));
+	let create_inherent_data_providers = move |_, ()| {
+		let last_timestamp = last_timestamp.clone();
+		async move {
+			let now = SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.expect("current time is after the unix epoch; qed")
+				.as_millis() as u64;
+			let next = |last: u64| now.max(last + slot_duration.as_millis() as u64);
+			let last = last_timestamp
+				.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(next(last)))
+				.expect("the update closure always returns `Some`; qed");
+			let timestamp = sp_timestamp::InherentDataProvider::new(next(last).into());
+
+			let slot =
+				sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+					*timestamp,
+					slot_duration,
+				);
+
+			Ok((timestamp, slot))
+		}
+	};
+
+	let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import: client.clone(),
+		env: proposer_factory,
+		client: client.clone(),
+		pool: transaction_pool,
+		commands_stream,
+		select_chain,
+		consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client))),
+		create_inherent_data_providers,
+	});
+
+	// the sealing task is considered essential, i.e. if it fails we take down the service
+	// with it.
+	task_manager.spawn_essential_handle().spawn_blocking(
+		"manual-seal",
+		Some("block-authoring"),
+		manual_seal,
+	);
+
+	Ok(())
+}
@@ -0,0 +1,24 @@
+use super::*;
+
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_system::RawOrigin;
+
+benchmarks! {
+	claim_address {
+		let caller: T::AccountId = whitelisted_caller();
+		let signature =
+			T::BenchmarkHelper::sign(&keccak_256(&Pallet::<T>::signable_message(&caller)));
+		let address = Pallet::<T>::recover_signer(&caller, &signature).ok_or("valid signature")?;
+
+		// the default account has funds to sweep
+		let _ = T::Currency::make_free_balance_be(
+			&T::DefaultAccount::convert(address),
+			T::Currency::minimum_balance() * 10u32.into(),
+		);
+	}: _(RawOrigin::Signed(caller.clone()), address, signature)
+	verify {
+		assert_eq!(Pallet::<T>::claimed_account(address), Some(caller));
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
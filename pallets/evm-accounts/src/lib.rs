@@ -0,0 +1,228 @@
+//! Maps EVM addresses to Substrate accounts and back.
+//!
+//! Every address starts out mapped to its default account, the address followed by 12 zero
+//! bytes, and each such account maps back to its address. An account that is not of that form
+//! has no address until it claims one with `claim_address`, signing its account id with the key
+//! of the address. A claimed address maps to the claiming account in both directions, and its
+//! default account no longer has an address, and the funds of the default account move to the
+//! claiming account.
+//!
+//! Either way each address has exactly one account and each account at most one address, so
+//! the EVM shows the same owner that Substrate does.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use frame_support::traits::{Contains, Currency, ExistenceRequirement};
+use sp_core::{crypto::AccountId32, H160};
+use sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
+use sp_runtime::traits::Convert;
+use sp_std::prelude::*;
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub mod weights;
+
+/// The text an address signs, followed by the hex encoded account id, to claim that account.
+pub const CLAIM_PREFIX: &[u8] = b"Map my kitties EVM address to the account 0x";
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	// --- CONFIG ---
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The currency swept from the default account of a claimed address.
+		type Currency: Currency<Self::AccountId>;
+		/// The account of an address that has not been claimed.
+		type DefaultAccount: Convert<H160, Self::AccountId>;
+		/// The inverse of `DefaultAccount`: `None` if the account is not the default account of
+		/// any address.
+		type DefaultAddress: Convert<Self::AccountId, Option<H160>>;
+		/// The accounts that have been used: those holding kitties or having sent transactions.
+		/// An address whose default account is in use cannot be claimed, as that would strand
+		/// what the default account holds. Funds alone do not count, as anyone can send them.
+		type InUse: Contains<Self::AccountId>;
+		/// Creates the signatures for the benchmarks of `claim_address`.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	// --- STORAGE ---
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	#[pallet::getter(fn claimed_account)]
+	/// The account that claimed an address.
+	pub type Accounts<T: Config> = StorageMap<_, Blake2_128Concat, H160, T::AccountId>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn claimed_address)]
+	/// The address an account claimed.
+	pub type Addresses<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, H160>;
+
+	// --- EVENTS ---
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An account claimed an address. \[account, address\]
+		AddressClaimed(T::AccountId, H160),
+	}
+
+	// --- ERRORS ---
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The signature was not made by the key of the address.
+		InvalidSignature,
+		/// The address has already been claimed.
+		AddressClaimed,
+		/// The account has already claimed an address.
+		AccountClaimed,
+		/// The account is the default account of an address, and already has that address.
+		DefaultAccount,
+		/// The default account of the address holds kitties or has sent transactions.
+		DefaultAccountInUse,
+	}
+
+	// --- CALLS ---
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Map `address` to the caller's account.
+		///
+		/// `signature` is the `personal_sign` signature of the address over `CLAIM_PREFIX`
+		/// followed by the hex encoded account id, as made by an Ethereum wallet. The free
+		/// balance of the default account of `address` is moved to the caller. A claim is
+		/// permanent.
+		#[pallet::weight(T::WeightInfo::claim_address())]
+		pub fn claim_address(
+			origin: OriginFor<T>,
+			address: H160,
+			signature: [u8; 65],
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(
+				Self::recover_signer(&who, &signature) == Some(address),
+				Error::<T>::InvalidSignature
+			);
+			ensure!(!Accounts::<T>::contains_key(address), Error::<T>::AddressClaimed);
+			ensure!(!Addresses::<T>::contains_key(&who), Error::<T>::AccountClaimed);
+			ensure!(T::DefaultAddress::convert(who.clone()).is_none(), Error::<T>::DefaultAccount);
+			let default_account = T::DefaultAccount::convert(address);
+			ensure!(!T::InUse::contains(&default_account), Error::<T>::DefaultAccountInUse);
+
+			// no one can sign for the default account, so what was sent to it belongs to the
+			// address
+			T::Currency::transfer(
+				&default_account,
+				&who,
+				T::Currency::free_balance(&default_account),
+				ExistenceRequirement::AllowDeath,
+			)?;
+
+			Accounts::<T>::insert(address, &who);
+			Addresses::<T>::insert(&who, address);
+
+			Self::deposit_event(Event::AddressClaimed(who, address));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The account of `address`: the account that claimed it, or else its default account.
+	pub fn account_of(address: H160) -> T::AccountId {
+		Accounts::<T>::get(address).unwrap_or_else(|| T::DefaultAccount::convert(address))
+	}
+
+	/// The address of `account`: the address it claimed, or else the address it is the default
+	/// account of, unless someone else claimed that address. `None` if it has neither.
+	pub fn address_of(account: &T::AccountId) -> Option<H160> {
+		Addresses::<T>::get(account).or_else(|| {
+			T::DefaultAddress::convert(account.clone())
+				.filter(|address| !Accounts::<T>::contains_key(address))
+		})
+	}
+
+	/// The message an address signs to be mapped to `who`.
+	pub fn signable_message(who: &T::AccountId) -> Vec<u8> {
+		let account = to_ascii_hex(&who.encode());
+
+		let mut length = Vec::new();
+		let mut n = CLAIM_PREFIX.len() + account.len();
+		loop {
+			length.push(b'0' + (n % 10) as u8);
+			n /= 10;
+			if n == 0 {
+				break
+			}
+		}
+		length.reverse();
+
+		let mut message = b"\x19Ethereum Signed Message:\n".to_vec();
+		message.extend_from_slice(&length);
+		message.extend_from_slice(CLAIM_PREFIX);
+		message.extend_from_slice(&account);
+		message
+	}
+
+	/// The address that signed the claim of `who`, if the signature is valid.
+	fn recover_signer(who: &T::AccountId, signature: &[u8; 65]) -> Option<H160> {
+		let message = keccak_256(&Self::signable_message(who));
+		let public = secp256k1_ecdsa_recover(signature, &message).ok()?;
+
+		Some(H160::from_slice(&keccak_256(&public)[12..]))
+	}
+}
+
+/// Creates the signatures for the benchmarks of `claim_address`.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper {
+	/// Sign the 32 byte `hash` with a new secp256k1 key pair, returning the signature with its
+	/// recovery id.
+	fn sign(hash: &[u8; 32]) -> [u8; 65];
+}
+
+/// The default account mapping: an address maps to the account made of the address followed
+/// by 12 zero bytes, and only those accounts map back.
+pub struct PaddedAccount;
+
+impl Convert<H160, AccountId32> for PaddedAccount {
+	fn convert(address: H160) -> AccountId32 {
+		let mut account = [0u8; 32];
+		account[..20].copy_from_slice(address.as_bytes());
+		account.into()
+	}
+}
+
+impl Convert<AccountId32, Option<H160>> for PaddedAccount {
+	fn convert(account: AccountId32) -> Option<H160> {
+		let bytes: &[u8; 32] = account.as_ref();
+		bytes[20..].iter().all(|b| *b == 0).then(|| H160::from_slice(&bytes[..20]))
+	}
+}
+
+fn to_ascii_hex(data: &[u8]) -> Vec<u8> {
+	let to_digit = |i: u8| if i < 10 { b'0' + i } else { b'a' + i - 10 };
+
+	data.iter().flat_map(|b| [to_digit(b >> 4), to_digit(b & 0xf)]).collect()
+}
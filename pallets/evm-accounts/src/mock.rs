@@ -0,0 +1,109 @@
+use crate as pallet_evm_accounts;
+
+use frame_support::traits::{ConstU16, ConstU32, ConstU64, Contains, Everything};
+use frame_system as system;
+use sp_core::{crypto::AccountId32, H256};
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		EvmAccounts: pallet_evm_accounts::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// The accounts that have sent transactions.
+pub struct AccountUsed;
+
+impl Contains<AccountId32> for AccountUsed {
+	fn contains(account: &AccountId32) -> bool {
+		System::account_nonce(account) > 0
+	}
+}
+
+impl pallet_evm_accounts::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type DefaultAccount = pallet_evm_accounts::PaddedAccount;
+	type DefaultAddress = pallet_evm_accounts::PaddedAccount;
+	type InUse = AccountUsed;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = MockBenchmarkHelper;
+	type WeightInfo = ();
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+pub struct MockBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_evm_accounts::BenchmarkHelper for MockBenchmarkHelper {
+	fn sign(hash: &[u8; 32]) -> [u8; 65] {
+		let secret = libsecp256k1::SecretKey::parse(&[1; 32]).unwrap();
+		let (signature, recovery_id) =
+			libsecp256k1::sign(&libsecp256k1::Message::parse(hash), &secret);
+
+		let mut result = [0u8; 65];
+		result[..64].copy_from_slice(&signature.serialize());
+		result[64] = recovery_id.serialize();
+		result
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t: sp_io::TestExternalities =
+		frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
+
+	t.execute_with(|| System::set_block_number(1));
+	t
+}
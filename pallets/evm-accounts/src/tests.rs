@@ -0,0 +1,160 @@
+use crate::{mock::*, Error, PaddedAccount};
+
+use frame_support::{assert_noop, assert_ok};
+use sp_core::{crypto::AccountId32, H160};
+use sp_io::hashing::keccak_256;
+use sp_runtime::traits::Convert;
+
+const ALICE: AccountId32 = AccountId32::new([1; 32]);
+const BOB: AccountId32 = AccountId32::new([2; 32]);
+
+fn secret(seed: u8) -> libsecp256k1::SecretKey {
+	libsecp256k1::SecretKey::parse(&keccak_256(&[seed])).unwrap()
+}
+
+fn address(secret: &libsecp256k1::SecretKey) -> H160 {
+	let public = libsecp256k1::PublicKey::from_secret_key(secret);
+	H160::from_slice(&keccak_256(&public.serialize()[1..65])[12..])
+}
+
+fn sign(secret: &libsecp256k1::SecretKey, who: &AccountId32) -> [u8; 65] {
+	let message = keccak_256(&EvmAccounts::signable_message(who));
+	let (signature, recovery_id) =
+		libsecp256k1::sign(&libsecp256k1::Message::parse(&message), secret);
+
+	let mut result = [0u8; 65];
+	result[..64].copy_from_slice(&signature.serialize());
+	result[64] = recovery_id.serialize() + 27;
+	result
+}
+
+#[test]
+fn should_map_addresses_to_default_accounts() {
+	new_test_ext().execute_with(|| {
+		let address = address(&secret(1));
+		let account: AccountId32 = PaddedAccount::convert(address);
+
+		assert_eq!(EvmAccounts::account_of(address), account);
+		assert_eq!(EvmAccounts::address_of(&account), Some(address));
+
+		// other accounts have no address until they claim one
+		assert_eq!(EvmAccounts::address_of(&ALICE), None);
+	});
+}
+
+#[test]
+fn should_claim_address() {
+	new_test_ext().execute_with(|| {
+		let key = secret(1);
+		let address = address(&key);
+
+		assert_ok!(EvmAccounts::claim_address(Origin::signed(ALICE), address, sign(&key, &ALICE)));
+
+		assert_eq!(EvmAccounts::account_of(address), ALICE);
+		assert_eq!(EvmAccounts::address_of(&ALICE), Some(address));
+		System::assert_last_event(Event::EvmAccounts(crate::Event::AddressClaimed(ALICE, address)));
+
+		// the default account of the address no longer maps back to it
+		assert_eq!(EvmAccounts::address_of(&PaddedAccount::convert(address)), None);
+	});
+}
+
+#[test]
+fn should_not_claim_address_without_its_signature() {
+	new_test_ext().execute_with(|| {
+		let key = secret(1);
+
+		// signed for another account
+		assert_noop!(
+			EvmAccounts::claim_address(Origin::signed(ALICE), address(&key), sign(&key, &BOB)),
+			Error::<Test>::InvalidSignature
+		);
+
+		// signed by another address
+		assert_noop!(
+			EvmAccounts::claim_address(
+				Origin::signed(ALICE),
+				address(&key),
+				sign(&secret(2), &ALICE)
+			),
+			Error::<Test>::InvalidSignature
+		);
+	});
+}
+
+#[test]
+fn should_claim_address_once() {
+	new_test_ext().execute_with(|| {
+		let (first, second) = (secret(1), secret(2));
+
+		assert_ok!(EvmAccounts::claim_address(
+			Origin::signed(ALICE),
+			address(&first),
+			sign(&first, &ALICE)
+		));
+
+		assert_noop!(
+			EvmAccounts::claim_address(Origin::signed(BOB), address(&first), sign(&first, &BOB)),
+			Error::<Test>::AddressClaimed
+		);
+		assert_noop!(
+			EvmAccounts::claim_address(
+				Origin::signed(ALICE),
+				address(&second),
+				sign(&second, &ALICE)
+			),
+			Error::<Test>::AccountClaimed
+		);
+	});
+}
+
+#[test]
+fn should_not_claim_address_for_default_account() {
+	new_test_ext().execute_with(|| {
+		let key = secret(1);
+		let default_account = PaddedAccount::convert(address(&secret(2)));
+
+		assert_noop!(
+			EvmAccounts::claim_address(
+				Origin::signed(default_account.clone()),
+				address(&key),
+				sign(&key, &default_account)
+			),
+			Error::<Test>::DefaultAccount
+		);
+	});
+}
+
+#[test]
+fn should_not_claim_address_whose_default_account_is_in_use() {
+	new_test_ext().execute_with(|| {
+		let key = secret(1);
+		System::inc_account_nonce(&PaddedAccount::convert(address(&key)));
+
+		assert_noop!(
+			EvmAccounts::claim_address(Origin::signed(ALICE), address(&key), sign(&key, &ALICE)),
+			Error::<Test>::DefaultAccountInUse
+		);
+	});
+}
+
+#[test]
+fn should_sweep_funds_of_default_account() {
+	new_test_ext().execute_with(|| {
+		let key = secret(1);
+		let default_account = PaddedAccount::convert(address(&key));
+
+		// anyone can fund the default account, which does not keep the address from being claimed
+		assert_ok!(Balances::set_balance(Origin::root(), default_account.clone(), 100, 0));
+
+		assert_ok!(EvmAccounts::claim_address(
+			Origin::signed(ALICE),
+			address(&key),
+			sign(&key, &ALICE)
+		));
+
+		assert_eq!(Balances::free_balance(&ALICE), 100);
+		assert_eq!(Balances::free_balance(&default_account), 0);
+		assert!(!System::account_exists(&default_account));
+	});
+}
@@ -0,0 +1,73 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimated weights for pallet_evm_accounts
+//!
+//! THESE ARE NOT BENCHMARK RESULTS. Every weight, and every read and write count, is a hand
+//! estimate. Replace this file with the output of the command below before relying on it.
+
+// Command to generate the weights:
+// target/release/node-template
+// benchmark
+// --pallet
+// pallet_evm_accounts
+// --extrinsic
+// *
+// --template=.maintain/frame-weight-template.hbs
+// --execution=wasm
+// --wasm-execution=compiled
+// --output
+// pallets/evm-accounts/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_evm_accounts.
+pub trait WeightInfo {
+	fn claim_address() -> Weight;
+}
+
+/// Weights for pallet_evm_accounts using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: EvmAccounts Accounts (r:1 w:1)
+	// Storage: EvmAccounts Addresses (r:1 w:1)
+	// Storage: Kitties KittyCount (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn claim_address() -> Weight {
+		(92_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: EvmAccounts Accounts (r:1 w:1)
+	// Storage: EvmAccounts Addresses (r:1 w:1)
+	// Storage: Kitties KittyCount (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn claim_address() -> Weight {
+		(92_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+}
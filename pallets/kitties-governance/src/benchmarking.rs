@@ -0,0 +1,106 @@
+use super::*;
+
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, Hooks};
+use frame_system::RawOrigin;
+use sp_runtime::{traits::Bounded, Permill};
+
+fn sale_fee<T: Config>() -> ParamChangeOf<T> {
+	ParamChange::SaleFee(Permill::from_percent(10))
+}
+
+/// Fund `owner` and have it create `count` kitties, returning their ids. Moves to the next block,
+/// so the kitties can vote on the proposals made from then on.
+fn create_kitties<T: Config>(
+	owner: &T::AccountId,
+	count: u32,
+) -> Result<Vec<T::KittyIndex>, &'static str> {
+	let _ = <T as pallet_kitties::Config>::Currency::make_free_balance_be(
+		owner,
+		BalanceOf::<T>::max_value(),
+	);
+
+	let mut kitty_ids = Vec::new();
+	for _ in 0..count {
+		kitty_ids.push(pallet_kitties::Pallet::<T>::next_kitty_id());
+		pallet_kitties::Pallet::<T>::create(RawOrigin::Signed(owner.clone()).into())?;
+	}
+
+	let now = <frame_system::Pallet<T>>::block_number();
+	<frame_system::Pallet<T>>::set_block_number(now + 1u32.into());
+
+	Ok(kitty_ids)
+}
+
+/// Make a proposal of `proposer`, who must hold a kitty.
+fn propose<T: Config>(proposer: &T::AccountId) -> Result<ProposalIndex, &'static str> {
+	Pallet::<T>::propose(RawOrigin::Signed(proposer.clone()).into(), sale_fee::<T>())?;
+
+	Ok(Pallet::<T>::proposal_count() - 1)
+}
+
+benchmarks! {
+	propose {
+		let caller: T::AccountId = whitelisted_caller();
+		create_kitties::<T>(&caller, 1)?;
+	}: _(RawOrigin::Signed(caller), sale_fee::<T>())
+
+	vote {
+		let k in 1 .. T::MaxVoteKitties::get();
+
+		let caller: T::AccountId = whitelisted_caller();
+		let kitty_ids = create_kitties::<T>(&caller, k)?;
+		let index = propose::<T>(&caller)?;
+	}: _(RawOrigin::Signed(caller), index, kitty_ids, true)
+
+	on_initialize {
+		let p in 1 .. T::MaxActiveProposals::get();
+
+		let caller: T::AccountId = whitelisted_caller();
+		create_kitties::<T>(&caller, 1)?;
+		for _ in 0..p {
+			propose::<T>(&caller)?;
+		}
+		// no proposal is due yet
+		let now = <frame_system::Pallet<T>>::block_number();
+	}: {
+		Pallet::<T>::on_initialize(now);
+	}
+
+	close {
+		let caller: T::AccountId = whitelisted_caller();
+		let kitty_ids = create_kitties::<T>(&caller, T::Quorum::get().max(1))?;
+		let index = propose::<T>(&caller)?;
+		Pallet::<T>::vote(RawOrigin::Signed(caller).into(), index, kitty_ids, true)?;
+
+		let end = Pallet::<T>::proposals(index).ok_or("proposal exists")?.end;
+		let now = end + 1u32.into();
+	}: {
+		Pallet::<T>::process(index, now);
+	}
+	verify {
+		assert!(matches!(
+			Pallet::<T>::proposals(index).map(|proposal| proposal.status),
+			Some(ProposalStatus::Approved(_))
+		));
+	}
+
+	enact {
+		let caller: T::AccountId = whitelisted_caller();
+		let kitty_ids = create_kitties::<T>(&caller, T::Quorum::get().max(1))?;
+		let index = propose::<T>(&caller)?;
+		Pallet::<T>::vote(RawOrigin::Signed(caller).into(), index, kitty_ids, true)?;
+
+		let end = Pallet::<T>::proposals(index).ok_or("proposal exists")?.end;
+		let closed_at = end + 1u32.into();
+		Pallet::<T>::process(index, closed_at);
+		let now = closed_at + T::EnactmentDelay::get();
+	}: {
+		Pallet::<T>::process(index, now);
+	}
+	verify {
+		assert_eq!(Pallet::<T>::proposals(index), None);
+	}
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
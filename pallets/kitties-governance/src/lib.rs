@@ -0,0 +1,444 @@
+//! Lets kitty holders change the marketplace parameters of `pallet_kitties`, such as the sale
+//! fee and the breeding cooldown.
+//!
+//! A holder proposes a `ParamChange`, reserving `ProposalDeposit`. For `VotingPeriod` blocks,
+//! holders vote with their kitties, each kitty weighing `VoteWeight::vote_weight` and voting at
+//! most once. Only kitties owned since before the block of the proposal, its snapshot, can vote,
+//! so moving a kitty to another account, even within that block, does not give it another vote. A
+//! proposal with at least `Quorum` votes, and more aye than nay votes, is enacted `EnactmentDelay`
+//! blocks after the end of its voting period. The deposit of an approved proposal is returned, and
+//! that of a rejected one slashed.
+//!
+//! The votes of closed proposals are removed in `on_idle`, as much as the block has room for.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	ensure,
+	pallet_prelude::{DispatchResult, RuntimeDebug},
+	traits::{Get, ReservableCurrency},
+	transactional,
+	weights::Weight,
+};
+use pallet_kitties::{BalanceOf, Kitty, ParamChange, ParamChangeOf};
+use scale_info::TypeInfo;
+use sp_io::KillStorageResult;
+use sp_runtime::{traits::Saturating, Permill};
+use sp_std::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	// --- CONFIG ---
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_kitties::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The number of blocks a proposal is open for voting.
+		#[pallet::constant]
+		type VotingPeriod: Get<Self::BlockNumber>;
+		/// The number of blocks between the end of the voting period of an approved proposal
+		/// and its enactment.
+		#[pallet::constant]
+		type EnactmentDelay: Get<Self::BlockNumber>;
+		/// The amount reserved from the proposer until the voting period ends. It is slashed if
+		/// the proposal is rejected.
+		#[pallet::constant]
+		type ProposalDeposit: Get<BalanceOf<Self>>;
+		/// The minimum number of votes, ayes plus nays, for a proposal to be approved.
+		#[pallet::constant]
+		type Quorum: Get<u32>;
+		/// The highest sale fee that can be proposed.
+		#[pallet::constant]
+		type MaxSaleFee: Get<Permill>;
+		/// The longest breeding cooldown that can be proposed.
+		#[pallet::constant]
+		type MaxBreedingCooldown: Get<Self::BlockNumber>;
+		/// The maximum number of proposals being voted on or waiting for enactment.
+		#[pallet::constant]
+		type MaxActiveProposals: Get<u32>;
+		/// The maximum number of kitties voting in a single `vote` call.
+		#[pallet::constant]
+		type MaxVoteKitties: Get<u32>;
+		/// The number of votes of each kitty.
+		type VoteWeight: KittyVoteWeight;
+		type WeightInfo: WeightInfo;
+	}
+
+	// --- STORAGE ---
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Stores the number of proposals made so far, used as the index of the next proposal.
+	#[pallet::storage]
+	#[pallet::getter(fn proposal_count)]
+	pub type ProposalCount<T: Config> = StorageValue<_, ProposalIndex, ValueQuery>;
+
+	/// Stores the proposals being voted on or waiting for enactment.
+	#[pallet::storage]
+	#[pallet::getter(fn proposals)]
+	pub type Proposals<T: Config> =
+		StorageMap<_, Twox64Concat, ProposalIndex, ProposalOf<T>, OptionQuery>;
+
+	/// Stores the indexes of the `Proposals`, in the order they were made.
+	#[pallet::storage]
+	#[pallet::getter(fn active_proposals)]
+	pub type ActiveProposals<T: Config> =
+		StorageValue<_, BoundedVec<ProposalIndex, T::MaxActiveProposals>, ValueQuery>;
+
+	/// Stores the kitties which voted on each proposal.
+	#[pallet::storage]
+	pub type VotedKitties<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		ProposalIndex,
+		Blake2_128Concat,
+		T::KittyIndex,
+		(),
+		OptionQuery,
+	>;
+
+	/// Stores the index of the first proposal whose `VotedKitties` may not be removed yet.
+	#[pallet::storage]
+	#[pallet::getter(fn votes_cleared_to)]
+	pub type VotesClearedTo<T: Config> = StorageValue<_, ProposalIndex, ValueQuery>;
+
+	// --- EVENTS ---
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A parameter change has been proposed. \[proposer, proposal_index, change, end\]
+		Proposed(T::AccountId, ProposalIndex, ParamChangeOf<T>, T::BlockNumber),
+		/// Kitties have voted on a proposal. \[voter, proposal_index, aye, votes\]
+		Voted(T::AccountId, ProposalIndex, bool, u32),
+		/// A proposal has been approved and will be enacted. \[proposal_index, enact_at\]
+		Approved(ProposalIndex, T::BlockNumber),
+		/// A proposal has been rejected, and its deposit slashed. \[proposal_index\]
+		Rejected(ProposalIndex),
+		/// An approved proposal has been enacted. \[proposal_index\]
+		Enacted(ProposalIndex),
+	}
+
+	// --- ERRORS ---
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The proposer owns no kitty.
+		NotKittyHolder,
+		/// There are already `MaxActiveProposals` proposals.
+		TooManyProposals,
+		/// The proposal does not exist, or its voting period is over.
+		NotVoting,
+		/// More than `MaxVoteKitties` kitties were passed to `vote`.
+		TooManyKitties,
+		/// The voter does not own one of the kitties.
+		NotOwner,
+		/// One of the kitties was acquired in the block of the snapshot of the proposal, or later.
+		AcquiredAfterSnapshot,
+		/// One of the kitties has already voted on the proposal.
+		AlreadyVoted,
+		/// The proposed sale fee or breeding cooldown is above `MaxSaleFee` or
+		/// `MaxBreedingCooldown`.
+		ChangeOutOfBounds,
+	}
+
+	// --- HOOKS ---
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut active = Self::active_proposals();
+			let mut weight = <T as Config>::WeightInfo::on_initialize(active.len() as u32);
+
+			let count = active.len();
+			active.retain(|index| {
+				let (used, still_active) = Self::process(*index, now);
+				weight = weight.saturating_add(used);
+				still_active
+			});
+
+			if active.len() != count {
+				ActiveProposals::<T>::put(active);
+				weight = weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+
+			weight
+		}
+
+		fn on_idle(_now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::clear_votes(remaining_weight)
+		}
+	}
+
+	// --- CALLS ---
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Propose `change`, reserving `ProposalDeposit` until the end of the voting period.
+		///
+		/// Only kitty holders can propose, and only changes within `MaxSaleFee` and
+		/// `MaxBreedingCooldown`.
+		#[pallet::weight(<T as Config>::WeightInfo::propose())]
+		#[transactional]
+		pub fn propose(origin: OriginFor<T>, change: ParamChangeOf<T>) -> DispatchResult {
+			let proposer = ensure_signed(origin)?;
+			ensure!(
+				pallet_kitties::Pallet::<T>::kitty_count(&proposer) > 0,
+				Error::<T>::NotKittyHolder
+			);
+			ensure!(Self::is_within_bounds(&change), Error::<T>::ChangeOutOfBounds);
+
+			let index = Self::proposal_count();
+			ActiveProposals::<T>::try_mutate(|active| active.try_push(index))
+				.map_err(|_| Error::<T>::TooManyProposals)?;
+
+			let deposit = T::ProposalDeposit::get();
+			<T as pallet_kitties::Config>::Currency::reserve(&proposer, deposit)?;
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			let end = now.saturating_add(T::VotingPeriod::get());
+			Proposals::<T>::insert(
+				index,
+				Proposal {
+					proposer: proposer.clone(),
+					change,
+					deposit,
+					snapshot: now,
+					end,
+					ayes: 0,
+					nays: 0,
+					voted: 0,
+					status: ProposalStatus::Voting,
+				},
+			);
+			ProposalCount::<T>::put(index.saturating_add(1));
+
+			Self::deposit_event(Event::Proposed(proposer, index, change, end));
+
+			Ok(())
+		}
+
+		/// Vote on a proposal with `kitty_ids`, which the sender must have owned since before the
+		/// snapshot of the proposal.
+		#[pallet::weight(<T as Config>::WeightInfo::vote(kitty_ids.len() as u32))]
+		#[transactional]
+		pub fn vote(
+			origin: OriginFor<T>,
+			proposal_index: ProposalIndex,
+			kitty_ids: Vec<T::KittyIndex>,
+			aye: bool,
+		) -> DispatchResult {
+			let voter = ensure_signed(origin)?;
+			ensure!(kitty_ids.len() as u32 <= T::MaxVoteKitties::get(), Error::<T>::TooManyKitties);
+
+			let mut proposal = Self::proposals(proposal_index).ok_or(Error::<T>::NotVoting)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				proposal.status == ProposalStatus::Voting && now <= proposal.end,
+				Error::<T>::NotVoting
+			);
+
+			let mut votes: u32 = 0;
+			for kitty_id in &kitty_ids {
+				let kitty = pallet_kitties::Pallet::<T>::kitties(&voter, kitty_id)
+					.ok_or(Error::<T>::NotOwner)?;
+				ensure!(
+					pallet_kitties::Pallet::<T>::owned_since(kitty_id) < proposal.snapshot,
+					Error::<T>::AcquiredAfterSnapshot
+				);
+				ensure!(
+					!VotedKitties::<T>::contains_key(proposal_index, kitty_id),
+					Error::<T>::AlreadyVoted
+				);
+
+				VotedKitties::<T>::insert(proposal_index, kitty_id, ());
+				votes = votes.saturating_add(T::VoteWeight::vote_weight(&kitty));
+			}
+
+			if aye {
+				proposal.ayes = proposal.ayes.saturating_add(votes);
+			} else {
+				proposal.nays = proposal.nays.saturating_add(votes);
+			}
+			proposal.voted = proposal.voted.saturating_add(kitty_ids.len() as u32);
+			Proposals::<T>::insert(proposal_index, proposal);
+
+			Self::deposit_event(Event::Voted(voter, proposal_index, aye, votes));
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Close the voting period of proposal `index`, or enact it, if it is due at `now`.
+	///
+	/// Returns the weight used, and whether the proposal is still active.
+	fn process(index: ProposalIndex, now: T::BlockNumber) -> (Weight, bool) {
+		let mut proposal = match Self::proposals(index) {
+			Some(proposal) => proposal,
+			None => return (0, false),
+		};
+
+		match proposal.status {
+			ProposalStatus::Voting if now > proposal.end => {
+				let weight = <T as Config>::WeightInfo::close();
+				let turnout = proposal.ayes.saturating_add(proposal.nays);
+
+				if turnout >= T::Quorum::get() && proposal.ayes > proposal.nays {
+					<T as pallet_kitties::Config>::Currency::unreserve(
+						&proposal.proposer,
+						proposal.deposit,
+					);
+
+					let enact_at = now.saturating_add(T::EnactmentDelay::get());
+					proposal.status = ProposalStatus::Approved(enact_at);
+					Proposals::<T>::insert(index, proposal);
+
+					Self::deposit_event(Event::Approved(index, enact_at));
+
+					(weight, true)
+				} else {
+					let _ = <T as pallet_kitties::Config>::Currency::slash_reserved(
+						&proposal.proposer,
+						proposal.deposit,
+					);
+					Proposals::<T>::remove(index);
+
+					Self::deposit_event(Event::Rejected(index));
+
+					(weight, false)
+				}
+			},
+			ProposalStatus::Approved(enact_at) if now >= enact_at => {
+				Proposals::<T>::remove(index);
+				pallet_kitties::Pallet::<T>::set_market_param(proposal.change);
+
+				Self::deposit_event(Event::Enacted(index));
+
+				(<T as Config>::WeightInfo::enact(), false)
+			},
+			_ => (0, true),
+		}
+	}
+
+	/// Whether `change` is within `MaxSaleFee` and `MaxBreedingCooldown`.
+	fn is_within_bounds(change: &ParamChangeOf<T>) -> bool {
+		match change {
+			ParamChange::SaleFee(fee) => *fee <= T::MaxSaleFee::get(),
+			ParamChange::BreedingCooldown(cooldown) => *cooldown <= T::MaxBreedingCooldown::get(),
+		}
+	}
+
+	/// Remove the `VotedKitties` of closed proposals, using at most `max_weight`.
+	///
+	/// Proposals close in the order they were made, so the votes are removed in that order too,
+	/// stopping at the first proposal still being voted on.
+	fn clear_votes(max_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let mut weight = db_weight.reads_writes(2, 1);
+		if weight > max_weight {
+			return 0
+		}
+
+		let count = Self::proposal_count();
+		let mut index = Self::votes_cleared_to();
+		while index < count {
+			// each proposal takes a read, and each removed vote a write
+			let left = max_weight.saturating_sub(weight).saturating_sub(db_weight.reads(1));
+			let limit = (left / db_weight.writes(1).max(1)).min(u32::MAX.into()) as u32;
+			if limit == 0 {
+				break
+			}
+
+			let voting = Self::proposals(index)
+				.map_or(false, |proposal| proposal.status == ProposalStatus::Voting);
+			weight = weight.saturating_add(db_weight.reads(1));
+			if voting {
+				break
+			}
+
+			let (removed, done) = match VotedKitties::<T>::remove_prefix(index, Some(limit)) {
+				KillStorageResult::AllRemoved(removed) => (removed, true),
+				KillStorageResult::SomeRemaining(removed) => (removed, false),
+			};
+			weight = weight.saturating_add(db_weight.writes(removed.into()));
+
+			if !done {
+				break
+			}
+			index = index.saturating_add(1);
+		}
+
+		VotesClearedTo::<T>::put(index);
+
+		weight
+	}
+}
+
+pub type ProposalIndex = u32;
+
+/// The number of votes of a kitty.
+///
+/// Only `OnePerKitty` is provided: the traits of generation-0 kitties are uniformly distributed,
+/// so there is no rarity to weight votes by yet.
+pub trait KittyVoteWeight {
+	fn vote_weight(kitty: &Kitty) -> u32;
+}
+
+/// One vote per kitty, so votes are weighted by the number of kitties held.
+pub struct OnePerKitty;
+
+impl KittyVoteWeight for OnePerKitty {
+	fn vote_weight(_kitty: &Kitty) -> u32 {
+		1
+	}
+}
+
+/// Where a proposal is in its lifecycle.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum ProposalStatus<BlockNumber> {
+	/// Open for voting until the end of the voting period.
+	Voting,
+	/// Approved, and enacted at the given block.
+	Approved(BlockNumber),
+}
+
+/// A proposed change of a marketplace parameter.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Proposal<AccountId, Balance, BlockNumber> {
+	pub proposer: AccountId,
+	pub change: ParamChange<BlockNumber>,
+	/// The amount reserved from the proposer.
+	pub deposit: Balance,
+	/// Only kitties owned since before this block can vote.
+	pub snapshot: BlockNumber,
+	/// The last block of the voting period.
+	pub end: BlockNumber,
+	pub ayes: u32,
+	pub nays: u32,
+	/// The number of kitties which voted.
+	pub voted: u32,
+	pub status: ProposalStatus<BlockNumber>,
+}
+
+pub type ProposalOf<T> = Proposal<
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	<T as frame_system::Config>::BlockNumber,
+>;
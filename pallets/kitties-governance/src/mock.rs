@@ -0,0 +1,179 @@
+use crate as pallet_kitties_governance;
+
+use frame_support::{
+	traits::{ConstU16, ConstU32, ConstU64, ConstU8, EqualPrivilegeOnly, Everything},
+	weights::IdentityFee,
+	PalletId,
+};
+use frame_system as system;
+use frame_system::EnsureRoot;
+use sp_core::H256;
+use sp_runtime::{
+	testing::{Header, TestSignature, UintAuthorityId},
+	traits::{BlakeTwo256, IdentityLookup},
+	Permill,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		KittiesGovernance: pallet_kitties_governance::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type TransactionByteFee = ConstU64<1>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = IdentityFee<u64>;
+	type FeeMultiplierUpdate = ();
+}
+
+frame_support::parameter_types! {
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+	pub const MaxSaleFee: Permill = Permill::from_percent(50);
+}
+
+impl pallet_scheduler::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = ConstU64<1_000_000_000>;
+	type ScheduleOrigin = EnsureRoot<u64>;
+	type MaxScheduledPerBlock = ConstU32<10>;
+	type WeightInfo = ();
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = ();
+	type NoPreimagePostponement = ();
+}
+
+impl pallet_kitties::Config for Test {
+	type Currency = Balances;
+	type Event = Event;
+	type KittyIndex = u32;
+	type Randomness = MockRandom;
+	type ForceOrigin = EnsureRoot<u64>;
+	type PauseOrigin = EnsureRoot<u64>;
+	type CommitDeposit = ConstU64<50>;
+	type RevealDelay = ConstU64<5>;
+	type RevealWindow = ConstU64<10>;
+	type HatchDelay = ConstU64<3>;
+	type MaxHatchesPerBlock = ConstU32<2>;
+	type MintBasePrice = ConstU64<0>;
+	type MintPriceIncrement = ConstU64<0>;
+	type MaxGen0Supply = ConstU32<100>;
+	type PalletId = KittiesPalletId;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
+	type MaxSponsoredPerBlock = ConstU32<2>;
+	type MaxSponsoredPerAccount = ConstU32<1>;
+	type ScheduledCall = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type ActionDeposit = ConstU64<10>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = MockBenchmarkHelper;
+	type WeightInfo = ();
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+pub struct MockBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_kitties::BenchmarkHelper<UintAuthorityId, TestSignature> for MockBenchmarkHelper {
+	fn create_public() -> UintAuthorityId {
+		UintAuthorityId(1000)
+	}
+
+	fn sign(public: &UintAuthorityId, message: &[u8]) -> TestSignature {
+		TestSignature(public.0, message.to_vec())
+	}
+}
+
+/// Randomness that is always zero, the DNA of the kitties is irrelevant to the votes.
+pub struct MockRandom;
+
+impl frame_support::traits::Randomness<H256, u64> for MockRandom {
+	fn random(_subject: &[u8]) -> (H256, u64) {
+		(H256::zero(), System::block_number())
+	}
+}
+
+impl pallet_kitties_governance::Config for Test {
+	type Event = Event;
+	type VotingPeriod = ConstU64<10>;
+	type EnactmentDelay = ConstU64<5>;
+	type ProposalDeposit = ConstU64<100>;
+	type Quorum = ConstU32<2>;
+	type MaxSaleFee = MaxSaleFee;
+	type MaxBreedingCooldown = ConstU64<100>;
+	type MaxActiveProposals = ConstU32<2>;
+	type MaxVoteKitties = ConstU32<3>;
+	type VoteWeight = pallet_kitties_governance::OnePerKitty;
+	type WeightInfo = ();
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(100, 500), (200, 500), (300, 500)] }
+		.assimilate_storage(&mut t)
+		.unwrap();
+
+	let mut t: sp_io::TestExternalities = t.into();
+
+	t.execute_with(|| System::set_block_number(1));
+	t
+}
@@ -0,0 +1,235 @@
+use super::{Error, ProposalStatus, VotedKitties};
+use crate::mock::*;
+
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use pallet_kitties::ParamChange;
+use sp_runtime::Permill;
+
+const SALE_FEE: ParamChange<u64> = ParamChange::SaleFee(Permill::from_percent(10));
+
+fn run_to_block(n: u64) {
+	for block in System::block_number() + 1..=n {
+		System::set_block_number(block);
+		KittiesGovernance::on_initialize(block);
+	}
+}
+
+/// Give `kitties` new kitties to `who`.
+fn create_kitties(who: u64, kitties: u32) {
+	for _ in 0..kitties {
+		assert_ok!(KittiesModule::create(Origin::signed(who)));
+	}
+}
+
+/// Move to the block after the kitties were created, so they can vote on the proposals made then.
+fn next_block() {
+	run_to_block(System::block_number() + 1);
+}
+
+#[test]
+fn should_propose_and_enact() {
+	new_test_ext().execute_with(|| {
+		// kitties 0 and 1 belong to #100, kitty 2 to #200
+		create_kitties(100, 2);
+		create_kitties(200, 1);
+		next_block();
+
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+		assert_eq!(Balances::reserved_balance(100), 100);
+		System::assert_last_event(Event::KittiesGovernance(crate::Event::Proposed(
+			100, 0, SALE_FEE, 12,
+		)));
+
+		assert_ok!(KittiesGovernance::vote(Origin::signed(100), 0, vec![0, 1], true));
+		assert_ok!(KittiesGovernance::vote(Origin::signed(200), 0, vec![2], false));
+		System::assert_last_event(Event::KittiesGovernance(crate::Event::Voted(200, 0, false, 1)));
+
+		// voting ends after block #12
+		run_to_block(12);
+		assert_eq!(KittiesGovernance::proposals(0).map(|p| p.status), Some(ProposalStatus::Voting));
+
+		run_to_block(13);
+		assert_eq!(
+			KittiesGovernance::proposals(0).map(|p| p.status),
+			Some(ProposalStatus::Approved(18))
+		);
+		assert_eq!(Balances::reserved_balance(100), 0);
+		assert_eq!(Balances::free_balance(100), 500);
+		System::assert_last_event(Event::KittiesGovernance(crate::Event::Approved(0, 18)));
+
+		run_to_block(17);
+		assert_eq!(KittiesModule::market_params().sale_fee, Permill::zero());
+
+		run_to_block(18);
+		assert_eq!(KittiesModule::market_params().sale_fee, Permill::from_percent(10));
+		assert_eq!(KittiesGovernance::proposals(0), None);
+		assert!(KittiesGovernance::active_proposals().is_empty());
+		System::assert_last_event(Event::KittiesGovernance(crate::Event::Enacted(0)));
+	});
+}
+
+#[test]
+fn should_reject_proposal_without_majority() {
+	new_test_ext().execute_with(|| {
+		create_kitties(100, 1);
+		create_kitties(200, 1);
+		next_block();
+
+		assert_ok!(KittiesGovernance::propose(
+			Origin::signed(100),
+			ParamChange::BreedingCooldown(20)
+		));
+		assert_ok!(KittiesGovernance::vote(Origin::signed(100), 0, vec![0], true));
+		assert_ok!(KittiesGovernance::vote(Origin::signed(200), 0, vec![1], false));
+
+		run_to_block(13);
+
+		assert_eq!(KittiesGovernance::proposals(0), None);
+		assert!(KittiesGovernance::active_proposals().is_empty());
+		// the deposit is slashed
+		assert_eq!(Balances::reserved_balance(100), 0);
+		assert_eq!(Balances::free_balance(100), 400);
+		System::assert_last_event(Event::KittiesGovernance(crate::Event::Rejected(0)));
+
+		run_to_block(21);
+		assert_eq!(KittiesModule::market_params().breeding_cooldown, 0);
+	});
+}
+
+#[test]
+fn should_reject_proposal_without_quorum() {
+	new_test_ext().execute_with(|| {
+		create_kitties(100, 1);
+		next_block();
+
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+		// a single vote is below the quorum of two
+		assert_ok!(KittiesGovernance::vote(Origin::signed(100), 0, vec![0], true));
+
+		run_to_block(13);
+
+		assert_eq!(KittiesGovernance::proposals(0), None);
+		assert_eq!(Balances::free_balance(100), 400);
+		System::assert_last_event(Event::KittiesGovernance(crate::Event::Rejected(0)));
+	});
+}
+
+#[test]
+fn should_clear_votes_of_closed_proposals() {
+	new_test_ext().execute_with(|| {
+		create_kitties(100, 3);
+		next_block();
+
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+		assert_ok!(KittiesGovernance::vote(Origin::signed(100), 0, vec![0, 1, 2], true));
+
+		// the votes are kept while the proposal is voted on
+		KittiesGovernance::on_idle(12, u64::MAX);
+		assert_eq!(VotedKitties::<Test>::iter_prefix(0).count(), 3);
+		assert_eq!(KittiesGovernance::votes_cleared_to(), 0);
+
+		run_to_block(13);
+
+		// at most two votes fit in this weight
+		KittiesGovernance::on_idle(13, 2);
+		assert_eq!(VotedKitties::<Test>::iter_prefix(0).count(), 1);
+		assert_eq!(KittiesGovernance::votes_cleared_to(), 0);
+
+		KittiesGovernance::on_idle(13, u64::MAX);
+		assert_eq!(VotedKitties::<Test>::iter_prefix(0).count(), 0);
+		assert_eq!(KittiesGovernance::votes_cleared_to(), 1);
+	});
+}
+
+#[test]
+fn should_vote_once_per_kitty() {
+	new_test_ext().execute_with(|| {
+		create_kitties(100, 2);
+		next_block();
+
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+		assert_ok!(KittiesGovernance::vote(Origin::signed(100), 0, vec![0], true));
+
+		assert_noop!(
+			KittiesGovernance::vote(Origin::signed(100), 0, vec![1, 0], true),
+			Error::<Test>::AlreadyVoted
+		);
+
+		// the kitty moved in the block of the snapshot can't vote again
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 200, 0));
+		assert_noop!(
+			KittiesGovernance::vote(Origin::signed(200), 0, vec![0], false),
+			Error::<Test>::AcquiredAfterSnapshot
+		);
+
+		assert_noop!(
+			KittiesGovernance::vote(Origin::signed(200), 0, vec![1], false),
+			Error::<Test>::NotOwner
+		);
+	});
+}
+
+#[test]
+fn should_not_vote_after_voting_period() {
+	new_test_ext().execute_with(|| {
+		create_kitties(100, 1);
+		next_block();
+
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+
+		run_to_block(13);
+		assert_noop!(
+			KittiesGovernance::vote(Origin::signed(100), 0, vec![0], true),
+			Error::<Test>::NotVoting
+		);
+	});
+}
+
+#[test]
+fn should_fail_propose_when_invalid() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			KittiesGovernance::propose(Origin::signed(100), SALE_FEE),
+			Error::<Test>::NotKittyHolder
+		);
+
+		create_kitties(100, 1);
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+		assert_ok!(KittiesGovernance::propose(Origin::signed(100), SALE_FEE));
+
+		// at most two proposals are active
+		assert_noop!(
+			KittiesGovernance::propose(Origin::signed(100), SALE_FEE),
+			Error::<Test>::TooManyProposals
+		);
+
+		assert_noop!(
+			KittiesGovernance::vote(Origin::signed(100), 0, vec![0, 1, 2, 3], true),
+			Error::<Test>::TooManyKitties
+		);
+	});
+}
+
+#[test]
+fn should_not_propose_change_out_of_bounds() {
+	new_test_ext().execute_with(|| {
+		create_kitties(100, 1);
+
+		assert_noop!(
+			KittiesGovernance::propose(
+				Origin::signed(100),
+				ParamChange::SaleFee(Permill::from_percent(51))
+			),
+			Error::<Test>::ChangeOutOfBounds
+		);
+		assert_noop!(
+			KittiesGovernance::propose(Origin::signed(100), ParamChange::BreedingCooldown(101)),
+			Error::<Test>::ChangeOutOfBounds
+		);
+
+		assert_ok!(KittiesGovernance::propose(
+			Origin::signed(100),
+			ParamChange::SaleFee(Permill::from_percent(50))
+		));
+	});
+}
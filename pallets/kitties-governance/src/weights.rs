@@ -0,0 +1,151 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimated weights for pallet_kitties_governance
+//!
+//! THESE ARE NOT BENCHMARK RESULTS. Every weight, and every read and write count, is a hand
+//! estimate. Replace this file with the output of the command below before relying on it.
+
+// Command to generate the weights:
+// target/release/node-template
+// benchmark
+// --pallet
+// pallet_kitties_governance
+// --extrinsic
+// *
+// --template=.maintain/frame-weight-template.hbs
+// --execution=wasm
+// --wasm-execution=compiled
+// --output
+// pallets/kitties-governance/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_kitties_governance.
+pub trait WeightInfo {
+	fn propose() -> Weight;
+	fn vote(k: u32, ) -> Weight;
+	fn on_initialize(p: u32, ) -> Weight;
+	fn close() -> Weight;
+	fn enact() -> Weight;
+}
+
+/// Weights for pallet_kitties_governance using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: Kitties KittyCount (r:1 w:0)
+	// Storage: KittiesGovernance ProposalCount (r:1 w:1)
+	// Storage: KittiesGovernance ActiveProposals (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: KittiesGovernance Proposals (r:0 w:1)
+	fn propose() -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: KittiesGovernance Proposals (r:1 w:1)
+	// Storage: Kitties Kitties (r:1 w:0)
+	// Storage: Kitties OwnedSince (r:1 w:0)
+	// Storage: KittiesGovernance VotedKitties (r:1 w:1)
+	fn vote(k: u32, ) -> Weight {
+		(21_000_000 as Weight)
+			// Standard Error: 9_000
+			.saturating_add((9_400_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+	}
+	// Storage: KittiesGovernance ActiveProposals (r:1 w:0)
+	// Storage: KittiesGovernance Proposals (r:1 w:0)
+	fn on_initialize(p: u32, ) -> Weight {
+		(2_900_000 as Weight)
+			// Standard Error: 2_000
+			.saturating_add((2_700_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(p as Weight)))
+	}
+	// Storage: System Account (r:1 w:1)
+	// Storage: KittiesGovernance Proposals (r:0 w:1)
+	fn close() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: KittiesGovernance Proposals (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:1)
+	fn enact() -> Weight {
+		(12_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: Kitties KittyCount (r:1 w:0)
+	// Storage: KittiesGovernance ProposalCount (r:1 w:1)
+	// Storage: KittiesGovernance ActiveProposals (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: KittiesGovernance Proposals (r:0 w:1)
+	fn propose() -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: KittiesGovernance Proposals (r:1 w:1)
+	// Storage: Kitties Kitties (r:1 w:0)
+	// Storage: Kitties OwnedSince (r:1 w:0)
+	// Storage: KittiesGovernance VotedKitties (r:1 w:1)
+	fn vote(k: u32, ) -> Weight {
+		(21_000_000 as Weight)
+			// Standard Error: 9_000
+			.saturating_add((9_400_000 as Weight).saturating_mul(k as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(k as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(k as Weight)))
+	}
+	// Storage: KittiesGovernance ActiveProposals (r:1 w:0)
+	// Storage: KittiesGovernance Proposals (r:1 w:0)
+	fn on_initialize(p: u32, ) -> Weight {
+		(2_900_000 as Weight)
+			// Standard Error: 2_000
+			.saturating_add((2_700_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(p as Weight)))
+	}
+	// Storage: System Account (r:1 w:1)
+	// Storage: KittiesGovernance Proposals (r:0 w:1)
+	fn close() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: KittiesGovernance Proposals (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:1)
+	fn enact() -> Weight {
+		(12_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+}
@@ -0,0 +1,25 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_kitties::{BreedingPreview, KittyOrDna};
+
+sp_api::decl_runtime_apis! {
+	/// Queries the state of the kitties pallet.
+	#[api_version(2)]
+	pub trait KittiesApi<Balance, KittyIndex> where
+		Balance: Codec,
+		KittyIndex: Codec,
+	{
+		/// The price of minting the next generation-0 kitty.
+		fn mint_price() -> Balance;
+
+		/// The probability of each gender and trait of the offspring of two kitties, or `None`
+		/// if either is the id of a kitty that does not exist.
+		fn preview_breeding(
+			kitty1: KittyOrDna<KittyIndex>,
+			kitty2: KittyOrDna<KittyIndex>,
+		) -> Option<BreedingPreview>;
+	}
+}
@@ -1,11 +1,13 @@
 use super::*;
 
 use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::Hooks;
 use frame_system::RawOrigin;
 
 benchmarks! {
 	create {
-		let caller = whitelisted_caller();
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
 	}: _(RawOrigin::Signed(caller))
 
 	breed {
@@ -15,14 +17,16 @@ benchmarks! {
 		let mut kitty = Kitty(Default::default());
 		let kitty_mom_id = Pallet::<T>::get_kitty_id()?;
 
-		pallet::Kitties::<T>::insert(&caller, kitty_mom_id, kitty.clone());
+		Pallet::<T>::insert_kitty(&caller, kitty_mom_id, &kitty);
 
 		// kitty_dad_dna = [1, 0, ..., 0]
 		kitty.0[0] = 1;
 		let kitty_dad_id = Pallet::<T>::get_kitty_id()?;
 
-		pallet::Kitties::<T>::insert(&caller, kitty_dad_id, kitty);
+		Pallet::<T>::insert_kitty(&caller, kitty_dad_id, &kitty);
 
+		// the breeding pair is put on a cooldown
+		Pallet::<T>::set_market_param(ParamChange::BreedingCooldown(10u32.into()));
 	}: _(RawOrigin::Signed(caller), kitty_mom_id, kitty_dad_id)
 
 	transfer {
@@ -30,15 +34,35 @@ benchmarks! {
 		let to = account("to", 0, 0);
 
 		let kitty_id = Pallet::<T>::get_kitty_id()?;
-		pallet::Kitties::<T>::insert(&caller, kitty_id, Kitty(Default::default()));
+		Pallet::<T>::insert_kitty(&caller, kitty_id, &Kitty(Default::default()));
 
 	}: _(RawOrigin::Signed(caller), to, kitty_id)
 
+	approve {
+		let caller = whitelisted_caller();
+		let approved: T::AccountId = account("approved", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&caller, kitty_id, &Kitty(Default::default()));
+
+	}: _(RawOrigin::Signed(caller), Some(approved), kitty_id)
+
+	transfer_from {
+		let caller: T::AccountId = whitelisted_caller();
+		let from: T::AccountId = account("from", 0, 0);
+		let to: T::AccountId = account("to", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&from, kitty_id, &Kitty(Default::default()));
+		Pallet::<T>::approve(RawOrigin::Signed(from.clone()).into(), Some(caller.clone()), kitty_id)?;
+
+	}: _(RawOrigin::Signed(caller), from, to, kitty_id)
+
 	set_price {
 		let caller = whitelisted_caller();
 
 		let kitty_id = Pallet::<T>::get_kitty_id()?;
-		pallet::Kitties::<T>::insert(&caller, kitty_id, Kitty(Default::default()));
+		Pallet::<T>::insert_kitty(&caller, kitty_id, &Kitty(Default::default()));
 
 	}: _(RawOrigin::Signed(caller), kitty_id, Some(100u32.into()))
 
@@ -49,10 +73,178 @@ benchmarks! {
 		let _ = T::Currency::make_free_balance_be(&caller, 1000u32.into());
 
 		let kitty_id = Pallet::<T>::get_kitty_id()?;
-		pallet::Kitties::<T>::insert(&seller, kitty_id, Kitty(Default::default()));
+		Pallet::<T>::insert_kitty(&seller, kitty_id, &Kitty(Default::default()));
 
 		Pallet::<T>::set_price(RawOrigin::Signed(seller.clone()).into(), kitty_id, Some(500u32.into()))?;
+
+		// the buyer pays a sale fee to the pallet account
+		Pallet::<T>::set_market_param(ParamChange::SaleFee(Permill::from_percent(10)));
 	}: _(RawOrigin::Signed(caller), seller, kitty_id, 500u32.into())
+
+	fill_order {
+		let caller = whitelisted_caller();
+		let public = T::BenchmarkHelper::create_public();
+
+		let _ = T::Currency::make_free_balance_be(&caller, 1000u32.into());
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		let order = SellOrder { kitty_id, price: 500u32.into(), expiry: 100u32.into(), nonce: 0 };
+		let signature = T::BenchmarkHelper::sign(&public, &Pallet::<T>::order_signing_payload(&order));
+
+		let seller: T::AccountId = public.into_account();
+		Pallet::<T>::insert_kitty(&seller, kitty_id, &Kitty(Default::default()));
+
+		Pallet::<T>::set_market_param(ParamChange::SaleFee(Permill::from_percent(10)));
+	}: _(RawOrigin::Signed(caller), seller, order, signature)
+
+	cancel_orders {
+		let caller = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller))
+
+	force_transfer {
+		let from: T::AccountId = account("from", 0, 0);
+		let to: T::AccountId = account("to", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&from, kitty_id, &Kitty(Default::default()));
+		pallet::KittyPrices::<T>::insert(kitty_id, BalanceOf::<T>::from(100u32));
+
+	}: _(RawOrigin::Root, from, to, kitty_id)
+
+	force_delist {
+		let owner: T::AccountId = account("owner", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&owner, kitty_id, &Kitty(Default::default()));
+		pallet::KittyPrices::<T>::insert(kitty_id, BalanceOf::<T>::from(100u32));
+
+	}: _(RawOrigin::Root, kitty_id)
+
+	force_burn {
+		let owner: T::AccountId = account("owner", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&owner, kitty_id, &Kitty(Default::default()));
+		pallet::KittyPrices::<T>::insert(kitty_id, BalanceOf::<T>::from(100u32));
+
+	}: _(RawOrigin::Root, owner, kitty_id)
+
+	set_paused {
+		let flags = PauseFlags { minting: true, breeding: true, trading: true };
+	}: _(RawOrigin::Root, flags)
+
+	set_presale {
+	}: _(RawOrigin::Root, [0u8; 32], 1u32.into(), 100u32.into())
+
+	claim {
+		let p in 0 .. MAX_MERKLE_PROOF_LEN as u32;
+
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+
+		let proof: Vec<[u8; 32]> = (0 .. p).map(|i| [i as u8; 32]).collect();
+		let root = Pallet::<T>::merkle_root(caller.using_encoded(blake2_256), &proof);
+
+		let now = frame_system::Pallet::<T>::block_number();
+		Presale::<T>::put(PresaleConfig { merkle_root: root, start: now, end: now + 100u32.into() });
+	}: _(RawOrigin::Signed(caller), proof)
+
+	commit {
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+
+		let commitment = T::Hashing::hash_of(&(&caller, [0u8; 32]));
+	}: _(RawOrigin::Signed(caller), CommitAction::Create, commitment)
+
+	reveal {
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+
+		let commitment = T::Hashing::hash_of(&(&caller, [0u8; 32]));
+		Pallet::<T>::commit(RawOrigin::Signed(caller.clone()).into(), CommitAction::Create, commitment)?;
+
+		let now = frame_system::Pallet::<T>::block_number();
+		frame_system::Pallet::<T>::set_block_number(now + T::RevealDelay::get() + 1u32.into());
+	}: _(RawOrigin::Signed(caller), [0u8; 32])
+
+	reap_commitment {
+		let caller: T::AccountId = whitelisted_caller();
+		let who: T::AccountId = account("who", 0, 0);
+		let _ = T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value());
+
+		let commitment = T::Hashing::hash_of(&(&who, [0u8; 32]));
+		Pallet::<T>::commit(RawOrigin::Signed(who.clone()).into(), CommitAction::Create, commitment)?;
+
+		let now = frame_system::Pallet::<T>::block_number();
+		frame_system::Pallet::<T>::set_block_number(
+			now + T::RevealDelay::get() + T::RevealWindow::get() + 1u32.into(),
+		);
+	}: _(RawOrigin::Signed(caller), who)
+
+	on_initialize {
+		let e in 0 .. T::MaxHatchesPerBlock::get();
+
+		let owner: T::AccountId = account("owner", 0, 0);
+		let hatch_at: T::BlockNumber = 1u32.into();
+
+		for _ in 0 .. e {
+			let kitty_id = Pallet::<T>::get_kitty_id()?;
+			let kind = EggKind::Bred { parents: (kitty_id, kitty_id), dna: Default::default() };
+			let egg = Egg { kind, hatch_at };
+
+			pallet::Eggs::<T>::insert(&owner, kitty_id, egg);
+			pallet::HatchQueue::<T>::mutate(hatch_at, |queue| queue.try_push((owner.clone(), kitty_id)))
+				.map_err(|_| "hatch queue is full")?;
+		}
+	}: {
+		Pallet::<T>::on_initialize(hatch_at);
+	}
+
+	schedule_action {
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+		let to: T::AccountId = account("to", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&caller, kitty_id, &Kitty(Default::default()));
+
+		let when = <frame_system::Pallet<T>>::block_number() + 10u32.into();
+	}: _(RawOrigin::Signed(caller), kitty_id, ScheduledAction::Transfer(to), when)
+
+	cancel_action {
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+		let to: T::AccountId = account("to", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&caller, kitty_id, &Kitty(Default::default()));
+
+		let when = <frame_system::Pallet<T>>::block_number() + 10u32.into();
+		Pallet::<T>::schedule_action(
+			RawOrigin::Signed(caller.clone()).into(),
+			kitty_id,
+			ScheduledAction::Transfer(to),
+			when,
+		)?;
+	}: _(RawOrigin::Signed(caller), kitty_id)
+
+	run_action {
+		let caller: T::AccountId = whitelisted_caller();
+		let _ = T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value());
+		let to: T::AccountId = account("to", 0, 0);
+
+		let kitty_id = Pallet::<T>::get_kitty_id()?;
+		Pallet::<T>::insert_kitty(&caller, kitty_id, &Kitty(Default::default()));
+
+		let when = <frame_system::Pallet<T>>::block_number() + 10u32.into();
+		Pallet::<T>::schedule_action(
+			RawOrigin::Signed(caller.clone()).into(),
+			kitty_id,
+			ScheduledAction::Transfer(to),
+			when,
+		)?;
+		frame_system::Pallet::<T>::set_block_number(when);
+	}: _(RawOrigin::Signed(caller), kitty_id)
 }
 
 impl_benchmark_test_suite!(Pallet, crate::tests::new_test_ext(), crate::tests::Tests,);
@@ -0,0 +1,392 @@
+use crate::{Approvals, Call, Config, Kitties, KittyPrices, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+	pallet_prelude::RuntimeDebug,
+	traits::{Contains, IsSubType},
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension, Zero},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	DispatchResult, FixedPointOperand,
+};
+use sp_std::marker::PhantomData;
+
+/// Rejects transactions whose call is not allowed by the runtime's `BaseCallFilter`.
+///
+/// The filter is otherwise only applied at dispatch, so a paused call would still be included
+/// in a block and charged for. Checking it in `validate` drops such calls from the pool.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckCallFilter<T: frame_system::Config + Send + Sync>(PhantomData<T>);
+
+impl<T: frame_system::Config + Send + Sync> CheckCallFilter<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: frame_system::Config + Send + Sync> Default for CheckCallFilter<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: frame_system::Config + Send + Sync> sp_std::fmt::Debug for CheckCallFilter<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckCallFilter")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: frame_system::Config + Send + Sync> SignedExtension for CheckCallFilter<T> {
+	const IDENTIFIER: &'static str = "CheckCallFilter";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if <T as frame_system::Config>::BaseCallFilter::contains(call) {
+			Ok(Default::default())
+		} else {
+			InvalidTransaction::Call.into()
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
+
+type PaymentBalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::Balance;
+
+/// The number of blocks a sponsored transaction stays valid in the pool.
+const SPONSORED_TRANSACTION_LONGEVITY: u64 = 8;
+
+/// Charges the transaction fee like `ChargeTransactionPayment`, except for the first mints of
+/// new users, whose fee is paid by `Pallet::sponsor_account`.
+///
+/// A `create` call without tip is sponsored when `Pallet::can_sponsor_mint` allows it. The
+/// sponsored transaction gets the lowest priority and a tag unique to the sender, so each account
+/// has at most one sponsored transaction in the pool, and replays are still rejected by
+/// `CheckNonce`. The limits are checked again in `pre_dispatch`; past them the sender pays the
+/// fee as usual, and a sender without funds has its transaction dropped. A sponsored mint only
+/// counts towards the limits once it is dispatched successfully.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeSponsoredTransactionPayment<T: Config + pallet_transaction_payment::Config>(
+	#[codec(compact)] PaymentBalanceOf<T>,
+);
+
+impl<T: Config + pallet_transaction_payment::Config> ChargeSponsoredTransactionPayment<T> {
+	/// Utility constructor, used by the chain and by clients.
+	pub fn from(tip: PaymentBalanceOf<T>) -> Self {
+		Self(tip)
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> sp_std::fmt::Debug
+	for ChargeSponsoredTransactionPayment<T>
+{
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeSponsoredTransactionPayment<{:?}>", self.0)
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> ChargeSponsoredTransactionPayment<T>
+where
+	PaymentBalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	<T as frame_system::Config>::Call:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + IsSubType<Call<T>>,
+{
+	fn is_sponsored(&self, who: &T::AccountId, call: &<T as frame_system::Config>::Call) -> bool {
+		self.0.is_zero() &&
+			matches!(call.is_sub_type(), Some(Call::create { .. })) &&
+			Pallet::<T>::can_sponsor_mint(who)
+	}
+
+	/// Withdraw the fee of `call` from the sponsor pool.
+	fn withdraw_sponsored_fee(
+		&self,
+		call: &<T as frame_system::Config>::Call,
+		info: &DispatchInfoOf<<T as frame_system::Config>::Call>,
+		len: usize,
+	) -> Result<
+		<<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<
+			T,
+		>>::LiquidityInfo,
+		TransactionValidityError,
+	> {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.0);
+
+		<<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<
+			T,
+		>>::withdraw_fee(&Pallet::<T>::sponsor_account(), call, info, fee, self.0)
+	}
+}
+
+impl<T: Config + pallet_transaction_payment::Config> SignedExtension
+	for ChargeSponsoredTransactionPayment<T>
+where
+	PaymentBalanceOf<T>: Send + Sync + From<u64> + FixedPointOperand,
+	<T as frame_system::Config>::Call:
+		Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo> + IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "ChargeSponsoredTransactionPayment";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	/// The sponsored sender, if any, and the pre-dispatch data of `ChargeTransactionPayment`.
+	type Pre = (Option<T::AccountId>, <ChargeTransactionPayment<T> as SignedExtension>::Pre);
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		if !self.is_sponsored(who, call) {
+			return ChargeTransactionPayment::<T>::from(self.0).validate(who, call, info, len)
+		}
+
+		self.withdraw_sponsored_fee(call, info, len)?;
+
+		ValidTransaction::with_tag_prefix("KittiesSponsoredMint")
+			.priority(0)
+			.and_provides(who)
+			.longevity(SPONSORED_TRANSACTION_LONGEVITY)
+			.build()
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		if !self.is_sponsored(who, call) {
+			let pre =
+				ChargeTransactionPayment::<T>::from(self.0).pre_dispatch(who, call, info, len)?;
+			return Ok((None, pre))
+		}
+
+		let imbalance = self.withdraw_sponsored_fee(call, info, len)?;
+
+		// Refunds of unused weight go back to the sponsor pool.
+		Ok((Some(who.clone()), (self.0, Pallet::<T>::sponsor_account(), imbalance)))
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let (sponsored, pre) = match pre {
+			Some((sponsored, pre)) => (sponsored, Some(pre)),
+			None => (None, None),
+		};
+
+		if let (Some(who), Ok(())) = (sponsored, result) {
+			Pallet::<T>::note_sponsored_mint(&who);
+		}
+
+		ChargeTransactionPayment::<T>::post_dispatch(pre, info, post_info, len, result)
+	}
+}
+
+/// Custom `InvalidTransaction` codes returned by `CheckKittyCall`, for clients to map to a
+/// message.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+#[repr(u8)]
+pub enum InvalidKittyCall {
+	/// The sender does not own the kitty, or the seller of a `buy` or the `from` account of a
+	/// `transfer_from` does not.
+	NotOwner = 1,
+	/// Both kitties passed to `breed` have the same gender.
+	SameGender = 2,
+	/// The kitty passed to `buy` is not listed.
+	NotForSale = 3,
+	/// The bid passed to `buy` is below the listed price.
+	BidPriceTooLow = 4,
+	/// The buyer and the seller of a `buy` are the same account.
+	BuyerIsSeller = 5,
+	/// The sender of a `transfer_from` is neither the owner nor approved.
+	NotApproved = 6,
+	/// A kitty passed to `breed` is on a breeding cooldown.
+	OnCooldown = 7,
+}
+
+impl From<InvalidKittyCall> for InvalidTransaction {
+	fn from(error: InvalidKittyCall) -> Self {
+		InvalidTransaction::Custom(error as u8)
+	}
+}
+
+impl From<InvalidKittyCall> for TransactionValidityError {
+	fn from(error: InvalidKittyCall) -> Self {
+		InvalidTransaction::from(error).into()
+	}
+}
+
+/// Rejects `breed`, `transfer`, `transfer_from` and `buy` calls that would fail on the kitty
+/// ownership, cooldown and listing checks, before any fee is paid.
+///
+/// These checks are a few storage reads, so they are cheap enough to run in `validate`. The calls
+/// still run them on dispatch, for the state may change between validation and inclusion.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckKittyCall<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> CheckKittyCall<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for CheckKittyCall<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for CheckKittyCall<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckKittyCall")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> CheckKittyCall<T> {
+	fn check(who: &T::AccountId, call: &Call<T>) -> Result<(), InvalidKittyCall> {
+		match call {
+			Call::breed { kitty1_id, kitty2_id } => {
+				let kitty1 = Kitties::<T>::get(who, kitty1_id).ok_or(InvalidKittyCall::NotOwner)?;
+				let kitty2 = Kitties::<T>::get(who, kitty2_id).ok_or(InvalidKittyCall::NotOwner)?;
+
+				if kitty1_id == kitty2_id || kitty1.gender() == kitty2.gender() {
+					return Err(InvalidKittyCall::SameGender)
+				}
+				if !Pallet::<T>::is_ready_to_breed(*kitty1_id) ||
+					!Pallet::<T>::is_ready_to_breed(*kitty2_id)
+				{
+					return Err(InvalidKittyCall::OnCooldown)
+				}
+			},
+			Call::transfer { kitty_id, .. } =>
+				if !Kitties::<T>::contains_key(who, kitty_id) {
+					return Err(InvalidKittyCall::NotOwner)
+				},
+			Call::transfer_from { from, kitty_id, .. } => {
+				if !Kitties::<T>::contains_key(from, kitty_id) {
+					return Err(InvalidKittyCall::NotOwner)
+				}
+
+				if who != from && Approvals::<T>::get(kitty_id).as_ref() != Some(who) {
+					return Err(InvalidKittyCall::NotApproved)
+				}
+			},
+			Call::buy { seller, kitty_id, bid_price } => {
+				if who == seller {
+					return Err(InvalidKittyCall::BuyerIsSeller)
+				}
+
+				let price = KittyPrices::<T>::get(kitty_id).ok_or(InvalidKittyCall::NotForSale)?;
+
+				if *bid_price < price {
+					return Err(InvalidKittyCall::BidPriceTooLow)
+				}
+
+				if !Kitties::<T>::contains_key(seller, kitty_id) {
+					return Err(InvalidKittyCall::NotOwner)
+				}
+			},
+			_ => {},
+		}
+
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckKittyCall<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "CheckKittyCall";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		if let Some(call) = call.is_sub_type() {
+			Self::check(who, call)?;
+		}
+
+		Ok(Default::default())
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
@@ -2,16 +2,29 @@
 
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
-	pallet_prelude::{DispatchError, RuntimeDebug},
-	traits::{Currency, ExistenceRequirement, Randomness},
-	transactional, Parameter,
+	ensure,
+	pallet_prelude::{DispatchError, DispatchResult, RuntimeDebug},
+	traits::{
+		schedule::{DispatchTime, Named as ScheduleNamed, LOWEST_PRIORITY},
+		Currency, ExistenceRequirement, Get, Randomness, ReservableCurrency, StorageVersion,
+	},
+	transactional,
+	weights::Weight,
+	PalletId, Parameter,
 };
 use scale_info::TypeInfo;
-use sp_io::hashing::blake2_128;
+use sp_io::{
+	hashing::{blake2_128, blake2_256},
+	KillStorageResult,
+};
 use sp_runtime::{
-	traits::{AtLeast32BitUnsigned, Bounded, CheckedAdd, One},
-	ArithmeticError,
+	traits::{
+		AccountIdConversion, AtLeast32BitUnsigned, Bounded, CheckedAdd, Dispatchable,
+		Hash as HashT, IdentifyAccount, One, Saturating, Verify, Zero,
+	},
+	ArithmeticError, Perbill, Permill,
 };
+use sp_std::prelude::*;
 
 pub use pallet::*;
 
@@ -24,6 +37,13 @@ mod tests;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+mod extensions;
+pub use extensions::{
+	ChargeSponsoredTransactionPayment, CheckCallFilter, CheckKittyCall, InvalidKittyCall,
+};
+
+pub mod migrations;
+
 mod weights;
 pub use weights::WeightInfo;
 
@@ -39,16 +59,72 @@ pub mod pallet {
 	// --- CONFIG ---
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
-		type Currency: Currency<Self::AccountId>;
+		type Currency: ReservableCurrency<Self::AccountId>;
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 		type KittyIndex: AtLeast32BitUnsigned + Bounded + Copy + Default + MaxEncodedLen + Parameter;
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
+		/// The origin which may forcibly move, delist or burn kitties, and schedule the presale.
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+		/// The origin which may pause and unpause parts of the pallet.
+		type PauseOrigin: EnsureOrigin<Self::Origin>;
+		/// The amount reserved while a DNA commitment is pending.
+		#[pallet::constant]
+		type CommitDeposit: Get<BalanceOf<Self>>;
+		/// The number of blocks to wait after a commitment before it can be revealed.
+		///
+		/// Must be long enough for `Randomness` to include blocks after the commitment.
+		#[pallet::constant]
+		type RevealDelay: Get<Self::BlockNumber>;
+		/// The number of blocks after the reveal delay during which a commitment can be revealed.
+		#[pallet::constant]
+		type RevealWindow: Get<Self::BlockNumber>;
+		/// The number of blocks between breeding and the egg hatching.
+		#[pallet::constant]
+		type HatchDelay: Get<Self::BlockNumber>;
+		/// The maximum number of eggs hatched in a single block.
+		#[pallet::constant]
+		type MaxHatchesPerBlock: Get<u32>;
+		/// The price of the first generation-0 kitty.
+		#[pallet::constant]
+		type MintBasePrice: Get<BalanceOf<Self>>;
+		/// The amount the mint price grows by for every generation-0 kitty minted.
+		#[pallet::constant]
+		type MintPriceIncrement: Get<BalanceOf<Self>>;
+		/// The maximum number of generation-0 kitties that can ever be minted.
+		#[pallet::constant]
+		type MaxGen0Supply: Get<u32>;
+		/// The pallet id, used to derive the account receiving the mint proceeds.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// The signature type of off-chain sell orders.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+		/// The public key verifying `OffchainSignature`, identifying the seller's account.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+		/// The maximum number of mints the sponsor pool pays the fees of in a single block.
+		#[pallet::constant]
+		type MaxSponsoredPerBlock: Get<u32>;
+		/// The maximum number of mints the sponsor pool pays the fees of for a single account.
+		#[pallet::constant]
+		type MaxSponsoredPerAccount: Get<u32>;
+		/// The overarching call type, dispatched by `Scheduler` for the scheduled actions.
+		type ScheduledCall: Parameter + Dispatchable<Origin = Self::Origin> + From<Call<Self>>;
+		/// The caller origin of `ScheduledCall`, with which scheduled actions are dispatched.
+		type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+		/// Dispatches the scheduled transfers and listings at their block.
+		type Scheduler: ScheduleNamed<Self::BlockNumber, Self::ScheduledCall, Self::PalletsOrigin>;
+		/// The amount reserved while an action is scheduled.
+		#[pallet::constant]
+		type ActionDeposit: Get<BalanceOf<Self>>;
+		/// Creates signed sell orders for the benchmarks.
+		#[cfg(feature = "runtime-benchmarks")]
+		type BenchmarkHelper: BenchmarkHelper<Self::OffchainPublic, Self::OffchainSignature>;
 		type WeightInfo: WeightInfo;
 	}
 
 	// --- STORAGE ---
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Stores an index value used as an identifier for the new kitties.
@@ -56,6 +132,21 @@ pub mod pallet {
 	#[pallet::getter(fn next_kitty_id)]
 	pub type NextKittyId<T: Config> = StorageValue<_, T::KittyIndex, ValueQuery>;
 
+	/// Stores the number of generation-0 kitties minted so far.
+	#[pallet::storage]
+	#[pallet::getter(fn gen0_minted)]
+	pub type Gen0Minted<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+	/// Stores the allowlist and schedule of the presale, if any.
+	#[pallet::storage]
+	#[pallet::getter(fn presale)]
+	pub type Presale<T: Config> = StorageValue<_, PresaleOf<T>, OptionQuery>;
+
+	/// Stores the accounts which have claimed their presale kitty.
+	#[pallet::storage]
+	#[pallet::getter(fn claimed)]
+	pub type Claimed<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
 	/// Stores all the kitties.
 	#[pallet::storage]
 	#[pallet::getter(fn kitties)]
@@ -69,12 +160,137 @@ pub mod pallet {
 		OptionQuery,
 	>;
 
+	/// Stores the owner of each kitty.
+	#[pallet::storage]
+	#[pallet::getter(fn owner_of)]
+	pub type Owners<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId, OptionQuery>;
+
+	/// Stores the number of kitties owned by each account.
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_count)]
+	pub type KittyCount<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Stores the block since which each kitty is owned by its current owner.
+	///
+	/// Kitties acquired before this was recorded count as owned since the genesis.
+	#[pallet::storage]
+	#[pallet::getter(fn owned_since)]
+	pub type OwnedSince<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber, ValueQuery>;
+
+	/// Stores the parents of each kitty hatched from an egg.
+	///
+	/// Generation-0 kitties, and kitties hatched before this was recorded, have no entry.
+	#[pallet::storage]
+	#[pallet::getter(fn parents)]
+	pub type Parents<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, (T::KittyIndex, T::KittyIndex), OptionQuery>;
+
+	/// Stores the account approved to transfer each kitty with `transfer_from`, if any.
+	#[pallet::storage]
+	#[pallet::getter(fn approvals)]
+	pub type Approvals<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId, OptionQuery>;
+
 	/// Stores kitty's prices.
 	#[pallet::storage]
 	#[pallet::getter(fn kitty_prices)]
 	pub type KittyPrices<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::KittyIndex, BalanceOf<T>, OptionQuery>;
 
+	/// Stores the eggs waiting to hatch, by owner.
+	#[pallet::storage]
+	#[pallet::getter(fn eggs)]
+	pub type Eggs<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::KittyIndex,
+		EggOf<T>,
+		OptionQuery,
+	>;
+
+	/// Stores the eggs to hatch at each block.
+	#[pallet::storage]
+	#[pallet::getter(fn hatch_queue)]
+	pub type HatchQueue<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<(T::AccountId, T::KittyIndex), T::MaxHatchesPerBlock>,
+		ValueQuery,
+	>;
+
+	/// Stores the pending DNA commitment of each account.
+	#[pallet::storage]
+	#[pallet::getter(fn commitments)]
+	pub type Commitments<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, CommitmentOf<T>, OptionQuery>;
+
+	/// Stores the action each owner scheduled on a kitty until it is run or cancelled.
+	#[pallet::storage]
+	#[pallet::getter(fn scheduled_actions)]
+	pub type ScheduledActions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::KittyIndex,
+		PendingActionOf<T>,
+		OptionQuery,
+	>;
+
+	/// Stores the current sell order nonce of each seller.
+	#[pallet::storage]
+	#[pallet::getter(fn order_nonces)]
+	pub type OrderNonces<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Stores the hashes of the filled sell orders by expiry, until they expire.
+	#[pallet::storage]
+	pub type FilledOrders<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::BlockNumber, Identity, T::Hash, (), OptionQuery>;
+
+	/// Stores the earliest expiry of the filled sell orders that may not be pruned yet.
+	#[pallet::storage]
+	#[pallet::getter(fn filled_orders_pruned_to)]
+	pub type FilledOrdersPrunedTo<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	/// Stores the accounts which have ever owned a kitty or laid an egg, other than the kitties
+	/// minted with fees paid by the sponsor pool.
+	#[pallet::storage]
+	#[pallet::getter(fn ever_owned)]
+	pub type EverOwned<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+	/// Stores the number of mints paid by the sponsor pool for each account.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsored_mints)]
+	pub type SponsoredMints<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// Stores the number of mints paid by the sponsor pool in the given block.
+	#[pallet::storage]
+	#[pallet::getter(fn sponsored_in_block)]
+	pub type SponsoredInBlock<T: Config> = StorageValue<_, (T::BlockNumber, u32), ValueQuery>;
+
+	/// Stores the block from which each kitty can breed again.
+	#[pallet::storage]
+	#[pallet::getter(fn ready_to_breed_at)]
+	pub type ReadyToBreedAt<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber, ValueQuery>;
+
+	/// Stores the marketplace parameters.
+	#[pallet::storage]
+	#[pallet::getter(fn market_params)]
+	pub type Params<T: Config> = StorageValue<_, MarketParamsOf<T>, ValueQuery>;
+
+	/// Stores which parts of the pallet are currently paused.
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	pub type Paused<T: Config> = StorageValue<_, PauseFlags, ValueQuery>;
+
 	// --- EVENTS ---
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -83,12 +299,42 @@ pub mod pallet {
 		KittyCreated(T::AccountId, T::KittyIndex, Kitty),
 		/// A kitty is created from a breeding pair. \[owner, kitty_id, kitty\]
 		KittyCreatedByBreeding(T::AccountId, T::KittyIndex, Kitty),
+		/// An egg has been laid by a breeding pair. \[owner, kitty_id, hatch_at\]
+		EggLaid(T::AccountId, T::KittyIndex, T::BlockNumber),
 		/// A kitty has been transferred to another user. \[from, to, kitty_id\]
 		KittyTransferred(T::AccountId, T::AccountId, T::KittyIndex),
+		/// The approval for a kitty has been updated. \[owner, approved, kitty_id\]
+		KittyApproved(T::AccountId, Option<T::AccountId>, T::KittyIndex),
 		/// The price for a kitty has been updated. \[owner, kitty_id, price\]
 		KittyPriceUpdated(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
 		/// A kitty is sold. \[seller, buyer, kitty_id, price\]
 		KittySold(T::AccountId, T::AccountId, T::KittyIndex, BalanceOf<T>),
+		/// A kitty has been moved by the force origin. \[from, to, kitty_id\]
+		KittyForceTransferred(T::AccountId, T::AccountId, T::KittyIndex),
+		/// A kitty has been delisted by the force origin. \[kitty_id\]
+		KittyForceDelisted(T::KittyIndex),
+		/// A kitty has been burned by the force origin. \[owner, kitty_id\]
+		KittyForceBurned(T::AccountId, T::KittyIndex),
+		/// The pause flags have been updated. \[flags\]
+		PauseFlagsUpdated(PauseFlags),
+		/// The presale has been scheduled. \[merkle_root, start, end\]
+		PresaleScheduled([u8; 32], T::BlockNumber, T::BlockNumber),
+		/// An allowlisted account claimed its presale kitty. \[owner, kitty_id, kitty\]
+		KittyClaimed(T::AccountId, T::KittyIndex, Kitty),
+		/// A seller cancelled its open sell orders. \[seller, new_nonce\]
+		OrdersCancelled(T::AccountId, u32),
+		/// A DNA commitment has been made. \[who, action\]
+		DnaCommitted(T::AccountId, CommitAction<T::KittyIndex>),
+		/// An expired commitment has been removed and its deposit slashed. \[who, deposit\]
+		CommitmentReaped(T::AccountId, BalanceOf<T>),
+		/// A marketplace parameter has been changed. \[change\]
+		MarketParamChanged(ParamChangeOf<T>),
+		/// An action on a kitty has been scheduled. \[owner, kitty_id, action, when\]
+		ActionScheduled(T::AccountId, T::KittyIndex, ScheduledActionOf<T>, T::BlockNumber),
+		/// A scheduled action on a kitty has been cancelled. \[owner, kitty_id\]
+		ActionCancelled(T::AccountId, T::KittyIndex),
+		/// A scheduled action on a kitty has been run. \[owner, kitty_id, result\]
+		ActionRun(T::AccountId, T::KittyIndex, DispatchResult),
 	}
 
 	// --- ERRORS ---
@@ -100,29 +346,89 @@ pub mod pallet {
 		BuyerIsSeller,
 		NotForSale,
 		BidPriceTooLow,
+		MintingPaused,
+		BreedingPaused,
+		TradingPaused,
+		CommitmentExists,
+		NoCommitment,
+		RevealTooEarly,
+		CommitmentExpired,
+		CommitmentNotExpired,
+		InvalidReveal,
+		RandomnessNotReady,
+		HatchQueueFull,
+		Gen0SupplyExhausted,
+		InvalidPresaleSchedule,
+		PresaleNotActive,
+		PublicMintNotOpen,
+		AlreadyClaimed,
+		InvalidMerkleProof,
+		OrderExpired,
+		InvalidOrderNonce,
+		OrderAlreadyFilled,
+		InvalidSignature,
+		NotApproved,
+		KittyOnCooldown,
+		ScheduleInPast,
+		AlreadyScheduled,
+		NotScheduled,
+		ActionNotDue,
+	}
+
+	// --- HOOKS ---
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let queue = HatchQueue::<T>::take(now);
+			let hatched = queue.len() as u32;
+
+			for (owner, kitty_id) in queue {
+				Self::hatch(owner, kitty_id);
+			}
+
+			T::WeightInfo::on_initialize(hatched)
+		}
+
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::prune_filled_orders(now, remaining_weight)
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			migrations::v1::migrate::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			migrations::v1::post_migrate::<T>()
+		}
 	}
 
 	// --- CALLS ---
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Create a new kitty
+		/// Create a new kitty, paying the current mint price.
 		#[pallet::weight(T::WeightInfo::create())]
+		#[transactional]
 		pub fn create(origin: OriginFor<T>) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().minting, Error::<T>::MintingPaused);
+			Self::ensure_public_mint_open()?;
 
+			Self::pay_gen0_mint(&sender)?;
 			let kitty_id = Self::get_kitty_id()?;
 
 			let dna = Self::generate_kitty_dna(&sender);
 			let kitty = Kitty(dna);
 
-			Kitties::<T>::insert(&sender, kitty_id, &kitty);
+			Self::insert_kitty(&sender, kitty_id, &kitty);
 
 			Self::deposit_event(Event::KittyCreated(sender, kitty_id, kitty));
 
 			Ok(())
 		}
 
-		/// Breed kitties to create a new kitty
+		/// Breed kitties to lay an egg, which hatches into a new kitty after `HatchDelay`
+		/// blocks.
 		#[pallet::weight(T::WeightInfo::breed())]
 		pub fn breed(
 			origin: OriginFor<T>,
@@ -130,20 +436,25 @@ pub mod pallet {
 			kitty2_id: T::KittyIndex,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().breeding, Error::<T>::BreedingPaused);
 
 			let kitty1 = Self::kitties(&sender, kitty1_id).ok_or(Error::<T>::InvalidKittyId)?;
 			let kitty2 = Self::kitties(&sender, kitty2_id).ok_or(Error::<T>::InvalidKittyId)?;
 
 			ensure!(kitty1.gender() != kitty2.gender(), Error::<T>::SameGender);
+			Self::ensure_ready_to_breed(kitty1_id, kitty2_id)?;
 
 			let kitty_id = Self::get_kitty_id()?;
 
-			let dna = Self::combine_kitties_dna(&sender, kitty1.dna(), kitty2.dna());
-			let kitty = Kitty(dna);
-
-			Kitties::<T>::insert(&sender, kitty_id, &kitty);
-
-			Self::deposit_event(Event::KittyCreatedByBreeding(sender, kitty_id, kitty));
+			Self::lay_egg(
+				sender,
+				kitty_id,
+				EggKind::Bred {
+					parents: (kitty1_id, kitty2_id),
+					dna: (kitty1.dna(), kitty2.dna()),
+				},
+			)?;
+			Self::start_breeding_cooldown(kitty1_id, kitty2_id);
 
 			Ok(())
 		}
@@ -156,20 +467,66 @@ pub mod pallet {
 			kitty_id: T::KittyIndex,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
 
-			Kitties::<T>::try_mutate_exists(sender.clone(), kitty_id, |kitty| -> DispatchResult {
-				if sender == to {
-					ensure!(kitty.is_some(), Error::<T>::InvalidKittyId);
-					return Ok(());
-				}
+			ensure!(Kitties::<T>::contains_key(&sender, kitty_id), Error::<T>::InvalidKittyId);
+			if sender == to {
+				return Ok(())
+			}
 
-				let kitty = kitty.take().ok_or(Error::<T>::InvalidKittyId)?;
-				Kitties::<T>::insert(&to, kitty_id, kitty);
+			Self::move_kitty(&sender, &to, kitty_id)?;
 
-				Self::deposit_event(Event::KittyTransferred(sender, to, kitty_id));
+			Self::deposit_event(Event::KittyTransferred(sender, to, kitty_id));
 
-				Ok(())
-			})
+			Ok(())
+		}
+
+		/// Approve `approved` to transfer a kitty of the sender with `transfer_from`, or
+		/// remove the approval with `None`.
+		///
+		/// The approval is cleared whenever the kitty changes hands.
+		#[pallet::weight(T::WeightInfo::approve())]
+		pub fn approve(
+			origin: OriginFor<T>,
+			approved: Option<T::AccountId>,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
+			ensure!(Kitties::<T>::contains_key(&sender, kitty_id), Error::<T>::NotOwner);
+
+			Approvals::<T>::set(kitty_id, approved.clone());
+
+			Self::deposit_event(Event::KittyApproved(sender, approved, kitty_id));
+
+			Ok(())
+		}
+
+		/// Transfer a kitty of `from` to `to`, as `from` or as the account approved by `from`.
+		#[pallet::weight(T::WeightInfo::transfer_from())]
+		pub fn transfer_from(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			to: T::AccountId,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
+
+			ensure!(Kitties::<T>::contains_key(&from, kitty_id), Error::<T>::InvalidKittyId);
+			ensure!(
+				sender == from || Self::approvals(kitty_id).as_ref() == Some(&sender),
+				Error::<T>::NotApproved
+			);
+			if from == to {
+				return Ok(())
+			}
+
+			Self::move_kitty(&from, &to, kitty_id)?;
+
+			Self::deposit_event(Event::KittyTransferred(from, to, kitty_id));
+
+			Ok(())
 		}
 
 		/// Set a price for a kitty.
@@ -181,6 +538,7 @@ pub mod pallet {
 			new_price: Option<BalanceOf<T>>,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
 			ensure!(Kitties::<T>::contains_key(&sender, kitty_id), Error::<T>::NotOwner);
 
 			KittyPrices::<T>::mutate_exists(kitty_id, |price| *price = new_price);
@@ -199,6 +557,7 @@ pub mod pallet {
 			bid_price: BalanceOf<T>,
 		) -> DispatchResult {
 			let buyer = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
 
 			ensure!(buyer != seller, Error::<T>::BuyerIsSeller);
 
@@ -207,31 +566,511 @@ pub mod pallet {
 
 				ensure!(bid_price >= price, Error::<T>::BidPriceTooLow);
 
-				Kitties::<T>::try_mutate_exists(&seller, kitty_id, |kitty| -> DispatchResult {
-					let kitty = kitty.take().ok_or(Error::<T>::InvalidKittyId)?;
+				Self::do_sell(seller, buyer, kitty_id, price)
+			})
+		}
+
+		/// Buy a kitty through a sell order signed off chain by `seller`.
+		///
+		/// The signature is over `order_signing_payload(order)`. The order must not be expired,
+		/// filled, or cancelled by `cancel_orders`. Any listing of the kitty is removed. Filled
+		/// orders are remembered until they expire.
+		#[pallet::weight(T::WeightInfo::fill_order())]
+		#[transactional]
+		pub fn fill_order(
+			origin: OriginFor<T>,
+			seller: T::AccountId,
+			order: SellOrderOf<T>,
+			signature: T::OffchainSignature,
+		) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
 
-					Kitties::<T>::insert(&buyer, kitty_id, kitty);
+			ensure!(buyer != seller, Error::<T>::BuyerIsSeller);
 
-					Self::deposit_event(Event::KittyTransferred(
-						seller.clone(),
-						buyer.clone(),
-						kitty_id,
-					));
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now <= order.expiry, Error::<T>::OrderExpired);
+			ensure!(order.nonce == Self::order_nonces(&seller), Error::<T>::InvalidOrderNonce);
+
+			let order_hash = T::Hashing::hash_of(&(&seller, &order));
+			ensure!(
+				!FilledOrders::<T>::contains_key(order.expiry, order_hash),
+				Error::<T>::OrderAlreadyFilled
+			);
+
+			ensure!(
+				signature.verify(&Self::order_signing_payload(&order)[..], &seller),
+				Error::<T>::InvalidSignature
+			);
+
+			FilledOrders::<T>::insert(order.expiry, order_hash, ());
+			KittyPrices::<T>::remove(order.kitty_id);
+
+			Self::do_sell(seller, buyer, order.kitty_id, order.price)
+		}
 
-					Ok(())
+		/// Cancel all the open sell orders of the sender, by bumping its order nonce.
+		#[pallet::weight(T::WeightInfo::cancel_orders())]
+		pub fn cancel_orders(origin: OriginFor<T>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let nonce =
+				OrderNonces::<T>::try_mutate(&sender, |nonce| -> Result<u32, DispatchError> {
+					*nonce = nonce.checked_add(1).ok_or(ArithmeticError::Overflow)?;
+					Ok(*nonce)
 				})?;
 
-				T::Currency::transfer(&buyer, &seller, price, ExistenceRequirement::KeepAlive)?;
+			Self::deposit_event(Event::OrdersCancelled(sender, nonce));
 
-				Self::deposit_event(Event::KittySold(seller, buyer, kitty_id, price));
+			Ok(())
+		}
 
-				Ok(())
-			})
+		/// Move a kitty from `from` to `to`, regardless of the owner's consent.
+		/// Any listing of the kitty is removed.
+		#[pallet::weight(T::WeightInfo::force_transfer())]
+		pub fn force_transfer(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			to: T::AccountId,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			Self::move_kitty(&from, &to, kitty_id)?;
+			KittyPrices::<T>::remove(kitty_id);
+
+			Self::deposit_event(Event::KittyForceTransferred(from, to, kitty_id));
+
+			Ok(())
+		}
+
+		/// Remove the listing of a kitty.
+		#[pallet::weight(T::WeightInfo::force_delist())]
+		pub fn force_delist(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			ensure!(KittyPrices::<T>::contains_key(kitty_id), Error::<T>::NotForSale);
+			KittyPrices::<T>::remove(kitty_id);
+
+			Self::deposit_event(Event::KittyForceDelisted(kitty_id));
+
+			Ok(())
+		}
+
+		/// Destroy a kitty owned by `owner`, together with its listing.
+		#[pallet::weight(T::WeightInfo::force_burn())]
+		pub fn force_burn(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			kitty_id: T::KittyIndex,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			Self::remove_kitty(&owner, kitty_id)?;
+			KittyPrices::<T>::remove(kitty_id);
+			ReadyToBreedAt::<T>::remove(kitty_id);
+			Parents::<T>::remove(kitty_id);
+
+			Self::deposit_event(Event::KittyForceBurned(owner, kitty_id));
+
+			Ok(())
+		}
+
+		/// Replace the pause flags for minting, breeding and trading.
+		#[pallet::weight(T::WeightInfo::set_paused())]
+		pub fn set_paused(origin: OriginFor<T>, flags: PauseFlags) -> DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			Paused::<T>::put(flags);
+
+			Self::deposit_event(Event::PauseFlagsUpdated(flags));
+
+			Ok(())
+		}
+
+		/// Schedule a presale from block `start` until block `end`, during which only the
+		/// accounts in the Merkle tree with root `merkle_root` can mint, using `claim`.
+		///
+		/// `create` is closed to everyone else until `end`.
+		#[pallet::weight(T::WeightInfo::set_presale())]
+		pub fn set_presale(
+			origin: OriginFor<T>,
+			merkle_root: [u8; 32],
+			start: T::BlockNumber,
+			end: T::BlockNumber,
+		) -> DispatchResult {
+			T::ForceOrigin::ensure_origin(origin)?;
+			ensure!(start < end, Error::<T>::InvalidPresaleSchedule);
+
+			Presale::<T>::put(PresaleConfig { merkle_root, start, end });
+
+			Self::deposit_event(Event::PresaleScheduled(merkle_root, start, end));
+
+			Ok(())
+		}
+
+		/// Mint a kitty during the presale, paying the current mint price.
+		///
+		/// `proof` are the sibling hashes from the sender's leaf up to the Merkle root. Each
+		/// account can claim once.
+		#[pallet::weight(T::WeightInfo::claim(proof.len() as u32))]
+		#[transactional]
+		pub fn claim(origin: OriginFor<T>, proof: Vec<[u8; 32]>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().minting, Error::<T>::MintingPaused);
+
+			let presale = Self::presale().ok_or(Error::<T>::PresaleNotActive)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(presale.start <= now && now < presale.end, Error::<T>::PresaleNotActive);
+
+			ensure!(!Self::claimed(&sender), Error::<T>::AlreadyClaimed);
+			ensure!(
+				proof.len() <= MAX_MERKLE_PROOF_LEN &&
+					Self::verify_merkle_proof(
+						presale.merkle_root,
+						sender.using_encoded(blake2_256),
+						&proof
+					),
+				Error::<T>::InvalidMerkleProof
+			);
+
+			Self::pay_gen0_mint(&sender)?;
+			let kitty_id = Self::get_kitty_id()?;
+
+			let kitty = Kitty(Self::generate_kitty_dna(&sender));
+			Self::insert_kitty(&sender, kitty_id, &kitty);
+			Claimed::<T>::insert(&sender, true);
+
+			Self::deposit_event(Event::KittyClaimed(sender, kitty_id, kitty));
+
+			Ok(())
+		}
+
+		/// Commit to minting or breeding a kitty whose DNA is revealed later.
+		///
+		/// `commitment` must be the hash of `(sender, secret)`, where `secret` is passed to
+		/// `reveal` after `RevealDelay` blocks. `CommitDeposit` is reserved until then.
+		/// An expired commitment of the sender is slashed and replaced.
+		#[pallet::weight(T::WeightInfo::commit())]
+		pub fn commit(
+			origin: OriginFor<T>,
+			action: CommitAction<T::KittyIndex>,
+			commitment: T::Hash,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_action_allowed(&sender, &action)?;
+
+			let now = <frame_system::Pallet<T>>::block_number();
+
+			if let Some(pending) = Self::commitments(&sender) {
+				ensure!(Self::is_expired(&pending, now), Error::<T>::CommitmentExists);
+				Self::reap(&sender, pending);
+			}
+
+			let deposit = T::CommitDeposit::get();
+			T::Currency::reserve(&sender, deposit)?;
+
+			Commitments::<T>::insert(
+				&sender,
+				Commitment { hash: commitment, action: action.clone(), block: now, deposit },
+			);
+
+			Self::deposit_event(Event::DnaCommitted(sender, action));
+
+			Ok(())
+		}
+
+		/// Reveal the secret of a pending commitment and mint the kitty, or lay the egg of the
+		/// bred kitty.
+		///
+		/// The DNA mixes `secret` with randomness that was unknown at commitment time. A bred
+		/// egg hatches after `HatchDelay` blocks, like those of `breed`.
+		#[pallet::weight(T::WeightInfo::reveal())]
+		#[transactional]
+		pub fn reveal(origin: OriginFor<T>, secret: [u8; 32]) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let pending = Self::commitments(&sender).ok_or(Error::<T>::NoCommitment)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+
+			ensure!(
+				now >= pending.block.saturating_add(T::RevealDelay::get()),
+				Error::<T>::RevealTooEarly
+			);
+			ensure!(!Self::is_expired(&pending, now), Error::<T>::CommitmentExpired);
+			ensure!(
+				T::Hashing::hash_of(&(&sender, secret)) == pending.hash,
+				Error::<T>::InvalidReveal
+			);
+			Self::ensure_action_allowed(&sender, &pending.action)?;
+
+			let (random, known_since) = T::Randomness::random(pending.hash.as_ref());
+			ensure!(known_since > pending.block, Error::<T>::RandomnessNotReady);
+
+			let selector = (random, secret, &sender).using_encoded(blake2_128);
+			let kitty_id = Self::get_kitty_id()?;
+
+			Commitments::<T>::remove(&sender);
+			T::Currency::unreserve(&sender, pending.deposit);
+
+			match pending.action {
+				CommitAction::Create => {
+					Self::pay_gen0_mint(&sender)?;
+
+					let kitty = Kitty(selector);
+					Self::insert_kitty(&sender, kitty_id, &kitty);
+
+					Self::deposit_event(Event::KittyCreated(sender, kitty_id, kitty));
+				},
+				CommitAction::Breed(kitty1_id, kitty2_id) => {
+					let kitty1 =
+						Self::kitties(&sender, kitty1_id).ok_or(Error::<T>::InvalidKittyId)?;
+					let kitty2 =
+						Self::kitties(&sender, kitty2_id).ok_or(Error::<T>::InvalidKittyId)?;
+
+					Self::lay_egg(
+						sender,
+						kitty_id,
+						EggKind::Revealed {
+							parents: (kitty1_id, kitty2_id),
+							dna: Self::combine_kitties_dna(selector, kitty1.dna(), kitty2.dna()),
+						},
+					)?;
+					Self::start_breeding_cooldown(kitty1_id, kitty2_id);
+				},
+			}
+
+			Ok(())
+		}
+
+		/// Remove an expired commitment of `who` and slash its deposit.
+		#[pallet::weight(T::WeightInfo::reap_commitment())]
+		pub fn reap_commitment(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let pending = Self::commitments(&who).ok_or(Error::<T>::NoCommitment)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+
+			ensure!(Self::is_expired(&pending, now), Error::<T>::CommitmentNotExpired);
+
+			Self::reap(&who, pending);
+
+			Ok(())
+		}
+
+		/// Schedule `action` on a kitty of the sender, dispatched as the sender at block `when`.
+		///
+		/// The action is run by `run_action` as the matching `transfer` or `set_price` call, so
+		/// it fails if the sender no longer owns the kitty by then. Each owner can schedule a
+		/// single action per kitty. `ActionDeposit` is reserved until the action is run or
+		/// cancelled.
+		#[pallet::weight(T::WeightInfo::schedule_action())]
+		#[transactional]
+		pub fn schedule_action(
+			origin: OriginFor<T>,
+			kitty_id: T::KittyIndex,
+			action: ScheduledActionOf<T>,
+			when: T::BlockNumber,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
+			ensure!(Kitties::<T>::contains_key(&sender, kitty_id), Error::<T>::NotOwner);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(when > now, Error::<T>::ScheduleInPast);
+			ensure!(
+				!ScheduledActions::<T>::contains_key(&sender, kitty_id),
+				Error::<T>::AlreadyScheduled
+			);
+
+			let deposit = T::ActionDeposit::get();
+			T::Currency::reserve(&sender, deposit)?;
+
+			let call: T::ScheduledCall = Call::<T>::run_action { kitty_id }.into();
+			T::Scheduler::schedule_named(
+				Self::schedule_id(&sender, kitty_id),
+				DispatchTime::At(when),
+				None,
+				LOWEST_PRIORITY,
+				frame_system::RawOrigin::Signed(sender.clone()).into(),
+				call,
+			)?;
+			ScheduledActions::<T>::insert(
+				&sender,
+				kitty_id,
+				PendingAction { action: action.clone(), when, deposit },
+			);
+
+			Self::deposit_event(Event::ActionScheduled(sender, kitty_id, action, when));
+
+			Ok(())
+		}
+
+		/// Cancel the action the sender scheduled on a kitty and unreserve its deposit.
+		///
+		/// Also releases the deposit of an action the scheduler dropped without running it.
+		#[pallet::weight(T::WeightInfo::cancel_action())]
+		pub fn cancel_action(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			let pending =
+				ScheduledActions::<T>::take(&sender, kitty_id).ok_or(Error::<T>::NotScheduled)?;
+			let _ = T::Scheduler::cancel_named(Self::schedule_id(&sender, kitty_id));
+			T::Currency::unreserve(&sender, pending.deposit);
+
+			Self::deposit_event(Event::ActionCancelled(sender, kitty_id));
+
+			Ok(())
+		}
+
+		/// Run the action the sender scheduled on a kitty, once its block has been reached.
+		///
+		/// Dispatched by the scheduler. The deposit is unreserved whether or not the action
+		/// succeeds, and the action's result is reported in `ActionRun`. While trading is paused
+		/// the action is not run, and its deposit stays reserved until it is cancelled.
+		#[pallet::weight(T::WeightInfo::run_action())]
+		pub fn run_action(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+			let sender = ensure_signed(origin.clone())?;
+			ensure!(!Self::paused().trading, Error::<T>::TradingPaused);
+
+			let pending =
+				Self::scheduled_actions(&sender, kitty_id).ok_or(Error::<T>::NotScheduled)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now >= pending.when, Error::<T>::ActionNotDue);
+
+			ScheduledActions::<T>::remove(&sender, kitty_id);
+			T::Currency::unreserve(&sender, pending.deposit);
+
+			let result = match pending.action {
+				ScheduledAction::Transfer(to) => Self::transfer(origin, to, kitty_id),
+				ScheduledAction::SetPrice(new_price) =>
+					Self::set_price(origin, kitty_id, new_price),
+			};
+
+			Self::deposit_event(Event::ActionRun(sender, kitty_id, result));
+
+			Ok(())
 		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
+	/// Whether `call` is rejected by the current pause flags.
+	///
+	/// Meant to be used by the runtime's `BaseCallFilter`.
+	pub fn is_call_paused(call: &Call<T>) -> bool {
+		let paused = Self::paused();
+
+		match call {
+			Call::create { .. } | Call::claim { .. } => paused.minting,
+			Call::breed { .. } => paused.breeding,
+			Call::commit { action: CommitAction::Create, .. } => paused.minting,
+			Call::commit { action: CommitAction::Breed(..), .. } => paused.breeding,
+			// the committed action is only known from the sender's commitment
+			Call::reveal { .. } => paused.minting || paused.breeding,
+			Call::transfer { .. } |
+			Call::approve { .. } |
+			Call::transfer_from { .. } |
+			Call::set_price { .. } |
+			Call::buy { .. } |
+			Call::fill_order { .. } |
+			Call::schedule_action { .. } |
+			Call::run_action { .. } => paused.trading,
+			_ => false,
+		}
+	}
+
+	/// The name of the task scheduled by `owner` for `kitty_id`.
+	fn schedule_id(owner: &T::AccountId, kitty_id: T::KittyIndex) -> Vec<u8> {
+		(T::PalletId::get(), kitty_id, owner).encode()
+	}
+
+	/// The account receiving the mint proceeds.
+	pub fn account_id() -> T::AccountId {
+		T::PalletId::get().into_account()
+	}
+
+	/// The account paying the transaction fees of sponsored mints.
+	///
+	/// Anyone can top it up with a regular transfer.
+	pub fn sponsor_account() -> T::AccountId {
+		T::PalletId::get().into_sub_account(b"sponsor")
+	}
+
+	/// Whether the sponsor pool may pay the fee of a mint by `who`.
+	///
+	/// Only accounts which never owned a kitty, apart from those of earlier sponsored mints, and
+	/// are under their sponsored mint cap are eligible, within the limit of sponsored mints per
+	/// block, and only when `create` would succeed: minting is open and `who` can pay the mint
+	/// price and stay alive. The pool pays the fee, never the mint price.
+	pub(crate) fn can_sponsor_mint(who: &T::AccountId) -> bool {
+		let price = Self::mint_price();
+		// `Currency::transfer` of nothing always succeeds
+		let can_pay = price.is_zero() ||
+			T::Currency::free_balance(who) >=
+				price.saturating_add(T::Currency::minimum_balance());
+
+		let sponsored = Self::sponsored_mints(who);
+
+		// `kitty_count` covers the owners from before `EverOwned`, who were never sponsored
+		!Self::ever_owned(who) &&
+			(sponsored > 0 || Self::kitty_count(who) == 0) &&
+			sponsored < T::MaxSponsoredPerAccount::get() &&
+			Self::sponsored_in_current_block() < T::MaxSponsoredPerBlock::get() &&
+			!Self::paused().minting &&
+			Self::ensure_public_mint_open().is_ok() &&
+			Self::gen0_minted() < T::MaxGen0Supply::get() &&
+			can_pay
+	}
+
+	/// Record that the sponsor pool paid for a successful mint by `who`.
+	///
+	/// The minted kitty doesn't count in `EverOwned`, which `who` was not in before the mint, so
+	/// that it stays eligible up to `MaxSponsoredPerAccount`.
+	pub(crate) fn note_sponsored_mint(who: &T::AccountId) {
+		SponsoredMints::<T>::mutate(who, |count| *count = count.saturating_add(1));
+		EverOwned::<T>::remove(who);
+		SponsoredInBlock::<T>::put((
+			<frame_system::Pallet<T>>::block_number(),
+			Self::sponsored_in_current_block().saturating_add(1),
+		));
+	}
+
+	/// The number of mints sponsored so far in the current block.
+	fn sponsored_in_current_block() -> u32 {
+		let (block, count) = Self::sponsored_in_block();
+
+		if block == <frame_system::Pallet<T>>::block_number() {
+			count
+		} else {
+			0
+		}
+	}
+
+	/// The price of the next generation-0 kitty.
+	///
+	/// The price grows linearly with the number of generation-0 kitties minted so far.
+	pub fn mint_price() -> BalanceOf<T> {
+		let minted: BalanceOf<T> = Self::gen0_minted().into();
+		T::MintBasePrice::get().saturating_add(T::MintPriceIncrement::get().saturating_mul(minted))
+	}
+
+	fn pay_gen0_mint(who: &T::AccountId) -> DispatchResult {
+		let minted = Self::gen0_minted();
+		ensure!(minted < T::MaxGen0Supply::get(), Error::<T>::Gen0SupplyExhausted);
+
+		T::Currency::transfer(
+			who,
+			&Self::account_id(),
+			Self::mint_price(),
+			ExistenceRequirement::KeepAlive,
+		)?;
+
+		Gen0Minted::<T>::put(minted + 1);
+
+		Ok(())
+	}
+
 	fn get_kitty_id() -> Result<T::KittyIndex, DispatchError> {
 		NextKittyId::<T>::try_mutate(|next_id| -> Result<T::KittyIndex, DispatchError> {
 			let id = *next_id;
@@ -240,14 +1079,301 @@ impl<T: Config> Pallet<T> {
 		})
 	}
 
+	fn ensure_action_allowed(
+		sender: &T::AccountId,
+		action: &CommitAction<T::KittyIndex>,
+	) -> DispatchResult {
+		let paused = Self::paused();
+
+		match action {
+			CommitAction::Create => {
+				ensure!(!paused.minting, Error::<T>::MintingPaused);
+				Self::ensure_public_mint_open()?;
+				ensure!(
+					Self::gen0_minted() < T::MaxGen0Supply::get(),
+					Error::<T>::Gen0SupplyExhausted
+				);
+			},
+			CommitAction::Breed(kitty1_id, kitty2_id) => {
+				ensure!(!paused.breeding, Error::<T>::BreedingPaused);
+
+				let kitty1 = Self::kitties(sender, kitty1_id).ok_or(Error::<T>::InvalidKittyId)?;
+				let kitty2 = Self::kitties(sender, kitty2_id).ok_or(Error::<T>::InvalidKittyId)?;
+
+				ensure!(kitty1.gender() != kitty2.gender(), Error::<T>::SameGender);
+				Self::ensure_ready_to_breed(*kitty1_id, *kitty2_id)?;
+			},
+		}
+
+		Ok(())
+	}
+
+	/// The bytes a seller signs to authorise `order`.
+	///
+	/// The genesis hash binds the order to this chain, so it can't be replayed on another chain
+	/// where the seller uses the same key.
+	pub fn order_signing_payload(order: &SellOrderOf<T>) -> Vec<u8> {
+		let genesis_hash = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero());
+		(ORDER_SIGNING_CONTEXT, genesis_hash, order).encode()
+	}
+
+	/// Remove the filled sell orders that expired before `now`, within `max_weight`.
+	///
+	/// An expired order can't be filled anyway, so its hash is no longer needed to reject it.
+	fn prune_filled_orders(now: T::BlockNumber, max_weight: Weight) -> Weight {
+		let db_weight = T::DbWeight::get();
+		let mut weight = db_weight.reads_writes(1, 1);
+		if weight > max_weight {
+			return 0
+		}
+
+		let mut expiry = Self::filled_orders_pruned_to();
+		while expiry < now {
+			// each expiry takes a read, and each removed order a write
+			let left = max_weight.saturating_sub(weight).saturating_sub(db_weight.reads(1));
+			let limit = (left / db_weight.writes(1).max(1)).min(u32::MAX.into()) as u32;
+			if limit == 0 {
+				break
+			}
+
+			let result = FilledOrders::<T>::remove_prefix(expiry, Some(limit));
+			let (removed, done) = match result {
+				KillStorageResult::AllRemoved(removed) => (removed, true),
+				KillStorageResult::SomeRemaining(removed) => (removed, false),
+			};
+			weight = weight.saturating_add(db_weight.reads_writes(1, removed.into()));
+
+			if !done {
+				break
+			}
+			expiry = expiry.saturating_add(One::one());
+		}
+
+		FilledOrdersPrunedTo::<T>::put(expiry);
+
+		weight
+	}
+
+	/// Store a new `kitty` owned by `owner`, keeping the ownership indexes up to date.
+	pub(crate) fn insert_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex, kitty: &Kitty) {
+		Kitties::<T>::insert(owner, kitty_id, kitty);
+		Owners::<T>::insert(kitty_id, owner);
+		OwnedSince::<T>::insert(kitty_id, <frame_system::Pallet<T>>::block_number());
+		KittyCount::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+		EverOwned::<T>::insert(owner, true);
+	}
+
+	/// Remove a kitty of `owner`, together with its approval.
+	fn remove_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) -> Result<Kitty, DispatchError> {
+		let kitty = Kitties::<T>::take(owner, kitty_id).ok_or(Error::<T>::InvalidKittyId)?;
+		Owners::<T>::remove(kitty_id);
+		OwnedSince::<T>::remove(kitty_id);
+		Approvals::<T>::remove(kitty_id);
+		KittyCount::<T>::mutate_exists(owner, |count| {
+			*count = count.and_then(|count| count.checked_sub(1)).filter(|count| *count > 0)
+		});
+
+		Ok(kitty)
+	}
+
+	/// Move a kitty from `from` to `to`, clearing its approval.
+	fn move_kitty(
+		from: &T::AccountId,
+		to: &T::AccountId,
+		kitty_id: T::KittyIndex,
+	) -> DispatchResult {
+		let kitty = Self::remove_kitty(from, kitty_id)?;
+		Self::insert_kitty(to, kitty_id, &kitty);
+
+		Ok(())
+	}
+
+	/// Move a kitty from `seller` to `buyer`, who pays `price` for it.
+	fn do_sell(
+		seller: T::AccountId,
+		buyer: T::AccountId,
+		kitty_id: T::KittyIndex,
+		price: BalanceOf<T>,
+	) -> DispatchResult {
+		Self::move_kitty(&seller, &buyer, kitty_id)?;
+
+		Self::deposit_event(Event::KittyTransferred(seller.clone(), buyer.clone(), kitty_id));
+
+		// The sale fee goes to the pallet account, like the mint proceeds.
+		let fee = Self::market_params().sale_fee * price;
+		T::Currency::transfer(
+			&buyer,
+			&seller,
+			price.saturating_sub(fee),
+			ExistenceRequirement::KeepAlive,
+		)?;
+		if !fee.is_zero() {
+			T::Currency::transfer(
+				&buyer,
+				&Self::account_id(),
+				fee,
+				ExistenceRequirement::KeepAlive,
+			)?;
+		}
+
+		Self::deposit_event(Event::KittySold(seller, buyer, kitty_id, price));
+
+		Ok(())
+	}
+
+	/// Whether `kitty_id` is not on a breeding cooldown.
+	pub fn is_ready_to_breed(kitty_id: T::KittyIndex) -> bool {
+		Self::ready_to_breed_at(kitty_id) <= <frame_system::Pallet<T>>::block_number()
+	}
+
+	fn ensure_ready_to_breed(kitty1_id: T::KittyIndex, kitty2_id: T::KittyIndex) -> DispatchResult {
+		ensure!(
+			Self::is_ready_to_breed(kitty1_id) && Self::is_ready_to_breed(kitty2_id),
+			Error::<T>::KittyOnCooldown
+		);
+
+		Ok(())
+	}
+
+	/// Put a breeding pair on the current breeding cooldown.
+	fn start_breeding_cooldown(kitty1_id: T::KittyIndex, kitty2_id: T::KittyIndex) {
+		let cooldown = Self::market_params().breeding_cooldown;
+		if cooldown.is_zero() {
+			return
+		}
+
+		let ready_at = <frame_system::Pallet<T>>::block_number().saturating_add(cooldown);
+		ReadyToBreedAt::<T>::insert(kitty1_id, ready_at);
+		ReadyToBreedAt::<T>::insert(kitty2_id, ready_at);
+	}
+
+	/// Change one of the marketplace parameters.
+	///
+	/// There is no call for this: the parameters are changed by the pallets governing the
+	/// marketplace, such as `pallet_kitties_governance`.
+	pub fn set_market_param(change: ParamChangeOf<T>) {
+		Params::<T>::mutate(|params| match change {
+			ParamChange::SaleFee(fee) => params.sale_fee = fee,
+			ParamChange::BreedingCooldown(cooldown) => params.breeding_cooldown = cooldown,
+		});
+
+		Self::deposit_event(Event::MarketParamChanged(change));
+	}
+
+	fn ensure_public_mint_open() -> DispatchResult {
+		if let Some(presale) = Self::presale() {
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(now >= presale.end, Error::<T>::PublicMintNotOpen);
+		}
+
+		Ok(())
+	}
+
+	/// Whether `leaf` is in the Merkle tree with the given `root`.
+	pub fn verify_merkle_proof(root: [u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+		Self::merkle_root(leaf, proof) == root
+	}
+
+	/// The root of the Merkle tree containing `leaf`, given the sibling hashes on its path.
+	///
+	/// Pairs are hashed with `blake2_256` in sorted order, so the proof needs no
+	/// left/right indications.
+	pub fn merkle_root(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+		proof.iter().fold(leaf, |node, sibling| {
+			if node <= *sibling {
+				blake2_256(&[node, *sibling].concat())
+			} else {
+				blake2_256(&[*sibling, node].concat())
+			}
+		})
+	}
+
+	fn is_expired(commitment: &CommitmentOf<T>, now: T::BlockNumber) -> bool {
+		now > commitment
+			.block
+			.saturating_add(T::RevealDelay::get())
+			.saturating_add(T::RevealWindow::get())
+	}
+
+	fn reap(who: &T::AccountId, commitment: CommitmentOf<T>) {
+		Commitments::<T>::remove(who);
+		let _ = T::Currency::slash_reserved(who, commitment.deposit);
+
+		Self::deposit_event(Event::CommitmentReaped(who.clone(), commitment.deposit));
+	}
+
 	fn generate_kitty_dna(sender: &T::AccountId) -> Dna {
 		let payload =
 			(T::Randomness::random_seed().0, &sender, <frame_system::Pallet<T>>::extrinsic_index());
 		payload.using_encoded(blake2_128)
 	}
 
-	fn combine_kitties_dna(sender: &T::AccountId, kitty1_dna: Dna, kitty2_dna: Dna) -> Dna {
-		let mut dna = Self::generate_kitty_dna(&sender);
+	/// Lay an egg of `owner` that hatches after `HatchDelay` blocks.
+	fn lay_egg(owner: T::AccountId, kitty_id: T::KittyIndex, kind: EggKindOf<T>) -> DispatchResult {
+		let now = <frame_system::Pallet<T>>::block_number();
+		let hatch_at =
+			Self::schedule_hatch(&owner, kitty_id, now.saturating_add(T::HatchDelay::get()))?;
+
+		Eggs::<T>::insert(&owner, kitty_id, Egg { kind, hatch_at });
+		EverOwned::<T>::insert(&owner, true);
+
+		Self::deposit_event(Event::EggLaid(owner, kitty_id, hatch_at));
+
+		Ok(())
+	}
+
+	/// Queue an egg at the first block from `hatch_at` with room left in the hatch queue.
+	fn schedule_hatch(
+		owner: &T::AccountId,
+		kitty_id: T::KittyIndex,
+		mut hatch_at: T::BlockNumber,
+	) -> Result<T::BlockNumber, DispatchError> {
+		for _ in 0..MAX_HATCH_SLOT_SEARCH {
+			let queued = HatchQueue::<T>::try_mutate(hatch_at, |queue| {
+				queue.try_push((owner.clone(), kitty_id))
+			});
+
+			if queued.is_ok() {
+				return Ok(hatch_at)
+			}
+
+			hatch_at = hatch_at.saturating_add(One::one());
+		}
+
+		Err(Error::<T>::HatchQueueFull.into())
+	}
+
+	fn hatch(owner: T::AccountId, kitty_id: T::KittyIndex) {
+		let egg = match Eggs::<T>::take(&owner, kitty_id) {
+			Some(egg) => egg,
+			None => return,
+		};
+
+		let (random, _) = T::Randomness::random(&kitty_id.encode());
+		let selector = (random, &owner, kitty_id).using_encoded(blake2_128);
+
+		match egg.kind {
+			EggKind::Bred { parents, dna } => {
+				let kitty = Kitty(Self::combine_kitties_dna(selector, dna.0, dna.1));
+				Self::insert_kitty(&owner, kitty_id, &kitty);
+				Parents::<T>::insert(kitty_id, parents);
+
+				Self::deposit_event(Event::KittyCreatedByBreeding(owner, kitty_id, kitty));
+			},
+			EggKind::Revealed { parents, dna } => {
+				let kitty = Kitty(dna);
+				Self::insert_kitty(&owner, kitty_id, &kitty);
+				Parents::<T>::insert(kitty_id, parents);
+
+				Self::deposit_event(Event::KittyCreatedByBreeding(owner, kitty_id, kitty));
+			},
+		}
+	}
+
+	/// Take each bit from `kitty1_dna` where `selector` is unset, and from `kitty2_dna` where
+	/// it is set.
+	fn combine_kitties_dna(selector: Dna, kitty1_dna: Dna, kitty2_dna: Dna) -> Dna {
+		let mut dna = selector;
 
 		for i in 0..dna.len() {
 			dna[i] = (!dna[i] & kitty1_dna[i]) | (dna[i] & kitty2_dna[i]);
@@ -255,19 +1381,289 @@ impl<T: Config> Pallet<T> {
 
 		dna
 	}
+
+	/// The probability of each gender and trait of the offspring of `kitty1` and `kitty2`, or
+	/// `None` if either is the id of a kitty that does not exist.
+	///
+	/// The gender and each trait are decoded from a single byte of the DNA, and each byte of the
+	/// selector of `combine_kitties_dna` is uniformly random, so combining the parents with each
+	/// possible selector byte gives the exact distribution.
+	pub fn preview_breeding(
+		kitty1: KittyOrDna<T::KittyIndex>,
+		kitty2: KittyOrDna<T::KittyIndex>,
+	) -> Option<BreedingPreview> {
+		let dna_of = |kitty| match kitty {
+			KittyOrDna::Id(kitty_id) =>
+				Self::kitties(Self::owner_of(kitty_id)?, kitty_id).map(|kitty| kitty.dna()),
+			KittyOrDna::Dna(dna) => Some(dna),
+		};
+		let (kitty1_dna, kitty2_dna) = (dna_of(kitty1)?, dna_of(kitty2)?);
+
+		let mut counts = (
+			[0u32; 2],
+			[0u32; BODY_COLOURS.len()],
+			[0u32; PATTERNS.len()],
+			[0u32; EYE_COLOURS.len()],
+		);
+		for selector in 0..=u8::MAX {
+			let kitty = Kitty(Self::combine_kitties_dna([selector; 16], kitty1_dna, kitty2_dna));
+			let traits = kitty.traits();
+
+			counts.0[kitty.gender() as usize] += 1;
+			counts.1[traits.body_colour as usize] += 1;
+			counts.2[traits.pattern as usize] += 1;
+			counts.3[traits.eye_colour as usize] += 1;
+		}
+
+		let probability = |count: u32| Perbill::from_rational(count, 256);
+		Some(BreedingPreview {
+			gender: counts.0.map(probability),
+			body_colour: counts.1.map(probability),
+			pattern: counts.2.map(probability),
+			eye_colour: counts.3.map(probability),
+		})
+	}
 }
 
-type Dna = [u8; 16];
+pub type Dna = [u8; 16];
+
+/// The current storage version, see `migrations`.
+const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+/// Prepended to sell orders before signing, so they can't be confused with other payloads.
+const ORDER_SIGNING_CONTEXT: &[u8] = b"kitties/sell-order";
+
+/// The maximum number of hashes in a presale Merkle proof.
+const MAX_MERKLE_PROOF_LEN: usize = 32;
+
+/// The number of consecutive blocks `lay_egg` tries when the hatch queue is full.
+const MAX_HATCH_SLOT_SEARCH: u32 = 16;
 
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
 pub struct Kitty(pub Dna);
 
+/// What a commitment is used for once revealed.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum CommitAction<KittyIndex> {
+	/// Mint a new kitty.
+	Create,
+	/// Breed the two given kitties.
+	Breed(KittyIndex, KittyIndex),
+}
+
+/// A kitty given by id, or by DNA for a kitty that does not exist.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum KittyOrDna<KittyIndex> {
+	Id(KittyIndex),
+	Dna(Dna),
+}
+
+/// The probability of each gender and trait of the offspring of two kitties, see
+/// `Pallet::preview_breeding`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct BreedingPreview {
+	/// Indexed by `KittyGender`.
+	pub gender: [Perbill; 2],
+	/// Indexed like `BODY_COLOURS`.
+	pub body_colour: [Perbill; BODY_COLOURS.len()],
+	/// Indexed like `PATTERNS`.
+	pub pattern: [Perbill; PATTERNS.len()],
+	/// Indexed like `EYE_COLOURS`.
+	pub eye_colour: [Perbill; EYE_COLOURS.len()],
+}
+
+/// An action on a kitty that its owner can schedule for a future block.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum ScheduledAction<AccountId, Balance> {
+	/// Transfer the kitty to the given account.
+	Transfer(AccountId),
+	/// Set the price of the kitty, or delist it with `None`.
+	SetPrice(Option<Balance>),
+}
+
+pub type ScheduledActionOf<T> =
+	ScheduledAction<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+/// An action scheduled on a kitty that has not been run yet.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct PendingAction<AccountId, Balance, BlockNumber> {
+	pub action: ScheduledAction<AccountId, Balance>,
+	/// The block the action is scheduled at.
+	pub when: BlockNumber,
+	pub deposit: Balance,
+}
+
+pub type PendingActionOf<T> = PendingAction<
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	<T as frame_system::Config>::BlockNumber,
+>;
+
+/// Creates keys and signatures for the benchmarks of `fill_order`.
+#[cfg(feature = "runtime-benchmarks")]
+pub trait BenchmarkHelper<Public, Signature> {
+	/// Generate a new key pair and return its public key.
+	fn create_public() -> Public;
+	/// Sign `message` with the key pair of `public`.
+	fn sign(public: &Public, message: &[u8]) -> Signature;
+}
+
+/// A sell order signed off chain by the kitty's owner.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct SellOrder<KittyIndex, Balance, BlockNumber> {
+	pub kitty_id: KittyIndex,
+	pub price: Balance,
+	/// The last block at which the order can be filled.
+	pub expiry: BlockNumber,
+	/// The seller's order nonce at signing time.
+	pub nonce: u32,
+}
+
+pub type SellOrderOf<T> =
+	SellOrder<<T as Config>::KittyIndex, BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+
+/// The allowlist and schedule of a presale.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct PresaleConfig<BlockNumber> {
+	/// The root of the Merkle tree of `blake2_256` hashes of the allowlisted accounts.
+	pub merkle_root: [u8; 32],
+	/// The first block of the presale.
+	pub start: BlockNumber,
+	/// The block at which `create` opens to everyone.
+	pub end: BlockNumber,
+}
+
+pub type PresaleOf<T> = PresaleConfig<<T as frame_system::Config>::BlockNumber>;
+
+/// A pending DNA commitment.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Commitment<Hash, KittyIndex, BlockNumber, Balance> {
+	/// Hash of the committer and the secret.
+	pub hash: Hash,
+	pub action: CommitAction<KittyIndex>,
+	/// The block the commitment was made at.
+	pub block: BlockNumber,
+	pub deposit: Balance,
+}
+
+pub type CommitmentOf<T> = Commitment<
+	<T as frame_system::Config>::Hash,
+	<T as Config>::KittyIndex,
+	<T as frame_system::Config>::BlockNumber,
+	BalanceOf<T>,
+>;
+
+/// How an egg was laid, which decides the DNA of the hatched kitty.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum EggKind<KittyIndex> {
+	/// The offspring of a breeding pair bred with `breed`.
+	Bred {
+		/// The ids of the breeding pair.
+		parents: (KittyIndex, KittyIndex),
+		/// The DNA of the breeding pair at the time of breeding.
+		dna: (Dna, Dna),
+	},
+	/// The offspring of a breeding pair revealed with `reveal`, whose DNA is already drawn.
+	Revealed {
+		/// The ids of the breeding pair.
+		parents: (KittyIndex, KittyIndex),
+		/// The DNA of the offspring.
+		dna: Dna,
+	},
+}
+
+pub type EggKindOf<T> = EggKind<<T as Config>::KittyIndex>;
+
+/// A bred kitty that has not hatched yet.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct Egg<KittyIndex, BlockNumber> {
+	/// How the egg was laid.
+	pub kind: EggKind<KittyIndex>,
+	/// The block at which the egg hatches.
+	pub hatch_at: BlockNumber,
+}
+
+pub type EggOf<T> = Egg<<T as Config>::KittyIndex, <T as frame_system::Config>::BlockNumber>;
+
+/// Parts of the pallet that can be paused independently.
+#[derive(
+	Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo,
+)]
+pub struct PauseFlags {
+	pub minting: bool,
+	pub breeding: bool,
+	pub trading: bool,
+}
+
+/// The marketplace parameters, changed with `Pallet::set_market_param`.
+#[derive(
+	Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo,
+)]
+pub struct MarketParams<BlockNumber> {
+	/// The part of the price of each sale paid to the pallet account rather than the seller.
+	pub sale_fee: Permill,
+	/// The number of blocks a breeding pair waits before either kitty can breed again.
+	pub breeding_cooldown: BlockNumber,
+}
+
+pub type MarketParamsOf<T> = MarketParams<<T as frame_system::Config>::BlockNumber>;
+
+/// A change of one of the `MarketParams`.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub enum ParamChange<BlockNumber> {
+	SaleFee(Permill),
+	BreedingCooldown(BlockNumber),
+}
+
+pub type ParamChangeOf<T> = ParamChange<<T as frame_system::Config>::BlockNumber>;
+
 #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
 pub enum KittyGender {
 	Female,
 	Male,
 }
 
+impl KittyGender {
+	pub fn name(&self) -> &'static str {
+		match self {
+			KittyGender::Female => "female",
+			KittyGender::Male => "male",
+		}
+	}
+}
+
+/// The names of the body colours, indexed by `KittyTraits::body_colour`.
+pub const BODY_COLOURS: [&str; 8] =
+	["black", "white", "ginger", "grey", "cream", "cinnamon", "lilac", "chocolate"];
+
+/// The names of the coat patterns, indexed by `KittyTraits::pattern`.
+pub const PATTERNS: [&str; 4] = ["solid", "tabby", "spotted", "calico"];
+
+/// The names of the eye colours, indexed by `KittyTraits::eye_colour`.
+pub const EYE_COLOURS: [&str; 4] = ["green", "blue", "amber", "copper"];
+
+/// The traits encoded in the DNA of a kitty, besides its gender.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct KittyTraits {
+	pub body_colour: u8,
+	pub pattern: u8,
+	pub eye_colour: u8,
+}
+
+impl KittyTraits {
+	pub fn body_colour_name(&self) -> &'static str {
+		BODY_COLOURS[self.body_colour as usize]
+	}
+
+	pub fn pattern_name(&self) -> &'static str {
+		PATTERNS[self.pattern as usize]
+	}
+
+	pub fn eye_colour_name(&self) -> &'static str {
+		EYE_COLOURS[self.eye_colour as usize]
+	}
+}
+
 impl Kitty {
 	pub fn dna(&self) -> Dna {
 		self.0
@@ -280,4 +1676,13 @@ impl Kitty {
 			KittyGender::Male
 		}
 	}
+
+	/// Decode the traits from the second to fourth bytes of the DNA.
+	pub fn traits(&self) -> KittyTraits {
+		KittyTraits {
+			body_colour: self.0[1] % BODY_COLOURS.len() as u8,
+			pattern: self.0[2] % PATTERNS.len() as u8,
+			eye_colour: self.0[3] % EYE_COLOURS.len() as u8,
+		}
+	}
 }
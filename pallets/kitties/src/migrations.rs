@@ -0,0 +1,54 @@
+//! Storage migrations of the kitties pallet.
+
+/// Version 1 adds the `Owners`, `KittyCount` and `Approvals` storage.
+pub mod v1 {
+	use crate::{Config, Kitties, KittyCount, Owners, Pallet};
+	#[cfg(feature = "try-runtime")]
+	use frame_support::ensure;
+	use frame_support::{
+		traits::{Get, GetStorageVersion, StorageVersion},
+		weights::Weight,
+	};
+
+	/// Build the `Owners` and `KittyCount` indexes from `Kitties`.
+	///
+	/// `Approvals` starts empty. Does nothing once the storage version is 1.
+	pub fn migrate<T: Config>() -> Weight {
+		if Pallet::<T>::on_chain_storage_version() >= 1 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut migrated: Weight = 0;
+
+		for (owner, kitty_id, _) in Kitties::<T>::iter() {
+			Owners::<T>::insert(kitty_id, &owner);
+			KittyCount::<T>::mutate(&owner, |count| *count = count.saturating_add(1));
+			migrated += 1;
+		}
+
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(2 * migrated + 1, 2 * migrated + 1)
+	}
+
+	/// Check that `Owners` and `KittyCount` match `Kitties` after the migration.
+	#[cfg(feature = "try-runtime")]
+	pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+		ensure!(Pallet::<T>::on_chain_storage_version() >= 1, "storage version is below 1");
+
+		let mut counts = sp_std::collections::btree_map::BTreeMap::new();
+		for (owner, kitty_id, _) in Kitties::<T>::iter() {
+			ensure!(Owners::<T>::get(kitty_id) == Some(owner.clone()), "kitty without owner");
+			*counts.entry(owner).or_insert(0u32) += 1;
+		}
+		ensure!(
+			Owners::<T>::iter().count() == counts.values().sum::<u32>() as usize,
+			"stray owner"
+		);
+		for (owner, count) in counts {
+			ensure!(KittyCount::<T>::get(&owner) == count, "wrong kitty count");
+		}
+
+		Ok(())
+	}
+}
@@ -2,12 +2,15 @@ use crate as pallet_kitties;
 
 use frame_support::{
 	parameter_types,
-	traits::{ConstU16, ConstU32, ConstU64, Everything, Randomness},
+	traits::{ConstU16, ConstU32, ConstU64, ConstU8, EqualPrivilegeOnly, Everything, Randomness},
+	weights::IdentityFee,
+	PalletId,
 };
 use frame_system as system;
+use frame_system::EnsureRoot;
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestSignature, UintAuthorityId},
 	traits::{BlakeTwo256, IdentityLookup},
 };
 
@@ -22,6 +25,8 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>},
 		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
 	}
 );
@@ -65,29 +70,91 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
+impl pallet_transaction_payment::Config for Test {
+	type OnChargeTransaction = pallet_transaction_payment::CurrencyAdapter<Balances, ()>;
+	type TransactionByteFee = ConstU64<1>;
+	type OperationalFeeMultiplier = ConstU8<5>;
+	type WeightToFee = IdentityFee<u64>;
+	type FeeMultiplierUpdate = ();
+}
+
 parameter_types! {
 	pub static MockRandom: H256 = Default::default();
+	pub static MintBasePrice: u64 = 0;
+	pub static MintPriceIncrement: u64 = 0;
+	pub static MaxGen0Supply: u32 = 100;
+	pub static MaxSponsoredPerAccount: u32 = 1;
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
 }
 
 impl Randomness<H256, u64> for MockRandom {
 	fn random(_subject: &[u8]) -> (H256, u64) {
-		(MockRandom::get(), 0)
+		(MockRandom::get(), System::block_number())
 	}
 }
 
+impl pallet_scheduler::Config for Test {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = ConstU64<1_000_000_000>;
+	type ScheduleOrigin = EnsureRoot<u64>;
+	type MaxScheduledPerBlock = ConstU32<10>;
+	type WeightInfo = ();
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = ();
+	type NoPreimagePostponement = ();
+}
+
 impl pallet_kitties::Config for Test {
 	type Currency = Balances;
 	type Event = Event;
 	type KittyIndex = u32;
 	type Randomness = MockRandom;
+	type ForceOrigin = EnsureRoot<u64>;
+	type PauseOrigin = EnsureRoot<u64>;
+	type CommitDeposit = ConstU64<50>;
+	type RevealDelay = ConstU64<5>;
+	type RevealWindow = ConstU64<10>;
+	type HatchDelay = ConstU64<3>;
+	type MaxHatchesPerBlock = ConstU32<2>;
+	type MintBasePrice = MintBasePrice;
+	type MintPriceIncrement = MintPriceIncrement;
+	type MaxGen0Supply = MaxGen0Supply;
+	type PalletId = KittiesPalletId;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
+	type MaxSponsoredPerBlock = ConstU32<2>;
+	type MaxSponsoredPerAccount = MaxSponsoredPerAccount;
+	type ScheduledCall = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type ActionDeposit = ConstU64<10>;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = MockBenchmarkHelper;
 	type WeightInfo = ();
 }
 
+#[cfg(feature = "runtime-benchmarks")]
+pub struct MockBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_kitties::BenchmarkHelper<UintAuthorityId, TestSignature> for MockBenchmarkHelper {
+	fn create_public() -> UintAuthorityId {
+		UintAuthorityId(1000)
+	}
+
+	fn sign(public: &UintAuthorityId, message: &[u8]) -> TestSignature {
+		TestSignature(public.0, message.to_vec())
+	}
+}
+
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
 
-	pallet_balances::GenesisConfig::<Test> { balances: vec![(200, 500)] }
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(200, 500), (300, 500)] }
 		.assimilate_storage(&mut t)
 		.unwrap();
 
@@ -1,8 +1,25 @@
-use super::{Error, Kitties, Kitty, KittyGender, KittyPrices};
+use super::{
+	BreedingPreview, ChargeSponsoredTransactionPayment, CheckKittyCall, CommitAction, EggKind,
+	Error, FilledOrders, HatchQueue, InvalidKittyCall, Kitties, Kitty, KittyCount, KittyGender,
+	KittyOrDna, KittyPrices, KittyTraits, NextKittyId, OwnedSince, ParamChange, PauseFlags,
+	ScheduledAction, SellOrder, SellOrderOf,
+};
 use crate::mock::*;
 
-use frame_support::{assert_noop, assert_ok};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GetStorageVersion, Hooks, StorageVersion},
+	weights::{GetDispatchInfo, PostDispatchInfo},
+};
 use sp_core::H256;
+use sp_io::hashing::blake2_256;
+use sp_runtime::{
+	testing::TestSignature,
+	traits::{BadOrigin, BlakeTwo256, Dispatchable, Hash, One, SignedExtension, Zero},
+	transaction_validity::{InvalidTransaction, TransactionValidity},
+	Perbill, Permill,
+};
 
 #[test]
 fn should_create_and_own_kitty() {
@@ -29,6 +46,86 @@ fn should_be_male_kitty() {
 	assert_eq!(Kitty([1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).gender(), KittyGender::Male);
 }
 
+#[test]
+fn should_decode_kitty_traits() {
+	let traits = Kitty([0, 10, 7, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).traits();
+
+	assert_eq!(traits, KittyTraits { body_colour: 2, pattern: 3, eye_colour: 1 });
+	assert_eq!(
+		(traits.body_colour_name(), traits.pattern_name(), traits.eye_colour_name()),
+		("ginger", "calico", "blue")
+	);
+}
+
+#[test]
+fn should_preview_uniform_breeding_of_opposite_dna() {
+	let preview =
+		KittiesModule::preview_breeding(KittyOrDna::Dna([0x00; 16]), KittyOrDna::Dna([0xff; 16]));
+
+	assert_eq!(
+		preview,
+		Some(BreedingPreview {
+			gender: [Perbill::from_percent(50); 2],
+			body_colour: [Perbill::from_rational(1u32, 8); 8],
+			pattern: [Perbill::from_percent(25); 4],
+			eye_colour: [Perbill::from_percent(25); 4],
+		})
+	);
+}
+
+#[test]
+fn should_preview_certain_breeding_of_same_dna() {
+	let dna = [1, 10, 7, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+	let preview = KittiesModule::preview_breeding(KittyOrDna::Dna(dna), KittyOrDna::Dna(dna))
+		.expect("previews of DNA always exist");
+
+	let certain = |index: usize, len: usize| {
+		let mut probabilities = vec![Perbill::zero(); len];
+		probabilities[index] = Perbill::one();
+		probabilities
+	};
+	assert_eq!(preview.gender.to_vec(), certain(KittyGender::Male as usize, 2));
+	assert_eq!(preview.body_colour.to_vec(), certain(2, 8));
+	assert_eq!(preview.pattern.to_vec(), certain(3, 4));
+	assert_eq!(preview.eye_colour.to_vec(), certain(1, 4));
+}
+
+#[test]
+fn should_preview_breeding_of_kitties() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		MockRandom::set(H256::from([2; 32]));
+		assert_ok!(KittiesModule::create(Origin::signed(101)));
+
+		let kitty1 = KittiesModule::kitties(100, 0).unwrap();
+		let kitty2 = KittiesModule::kitties(101, 1).unwrap();
+
+		// kitties of other owners may be previewed, and match their DNA
+		assert_eq!(
+			KittiesModule::preview_breeding(KittyOrDna::Id(0), KittyOrDna::Id(1)),
+			KittiesModule::preview_breeding(
+				KittyOrDna::Dna(kitty1.dna()),
+				KittyOrDna::Dna(kitty2.dna())
+			)
+		);
+		assert_eq!(KittiesModule::preview_breeding(KittyOrDna::Id(0), KittyOrDna::Id(2)), None);
+
+		// the hatched kitty has traits the preview deems possible
+		assert_ok!(KittiesModule::transfer(Origin::signed(101), 100, 1));
+		let preview =
+			KittiesModule::preview_breeding(KittyOrDna::Id(0), KittyOrDna::Id(1)).unwrap();
+		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
+		KittiesModule::on_initialize(4);
+
+		let kitty = KittiesModule::kitties(100, 2).unwrap();
+		let traits = kitty.traits();
+		assert!(!preview.gender[kitty.gender() as usize].is_zero());
+		assert!(!preview.body_colour[traits.body_colour as usize].is_zero());
+		assert!(!preview.pattern[traits.pattern as usize].is_zero());
+		assert!(!preview.eye_colour[traits.eye_colour as usize].is_zero());
+	});
+}
+
 #[test]
 fn should_create_kitty_from_breeding_pair() {
 	new_test_ext().execute_with(|| {
@@ -39,14 +136,35 @@ fn should_create_kitty_from_breeding_pair() {
 		MockRandom::set(H256::from([2; 32]));
 		assert_ok!(KittiesModule::create(Origin::signed(100)));
 
+		let (kitty0, kitty1) =
+			(KittiesModule::kitties(100, 0).unwrap(), KittiesModule::kitties(100, 1).unwrap());
+
 		// breed kitty_0 with kitty_1
 		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
 
+		assert_eq!(KittiesModule::kitties(100, 2), None);
+		assert_eq!(
+			KittiesModule::eggs(100, 2).map(|egg| egg.kind),
+			Some(EggKind::Bred { parents: (0, 1), dna: (kitty0.dna(), kitty1.dna()) })
+		);
+		assert_eq!(KittiesModule::next_kitty_id(), 3);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::EggLaid(100, 2, 4)));
+
+		// the egg hatches at block #4
+		KittiesModule::on_initialize(3);
+		assert_eq!(KittiesModule::kitties(100, 2), None);
+
+		KittiesModule::on_initialize(4);
+
 		let kitty =
-			Kitty([187, 250, 235, 118, 211, 247, 237, 253, 187, 239, 191, 185, 239, 171, 211, 122]);
+			Kitty([11, 178, 10, 86, 83, 163, 236, 109, 50, 228, 159, 184, 171, 170, 81, 72]);
 
 		assert_eq!(KittiesModule::kitties(100, 2), Some(kitty.clone()));
-		assert_eq!(KittiesModule::next_kitty_id(), 3);
+		assert_eq!(KittiesModule::eggs(100, 2), None);
+		assert_eq!(KittiesModule::parents(2), Some((0, 1)));
+		assert_eq!(KittiesModule::parents(0), None);
+		assert_eq!(HatchQueue::<Test>::contains_key(4), false);
 
 		System::assert_last_event(Event::KittiesModule(crate::Event::KittyCreatedByBreeding(
 			100, 2, kitty,
@@ -54,6 +172,59 @@ fn should_create_kitty_from_breeding_pair() {
 	});
 }
 
+#[test]
+fn should_delay_hatch_when_queue_full() {
+	new_test_ext().execute_with(|| {
+		Kitties::<Test>::insert(100, 0, Kitty([0; 16]));
+		Kitties::<Test>::insert(100, 1, Kitty([1; 16]));
+		NextKittyId::<Test>::put(2);
+
+		// at most two eggs hatch per block
+		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
+		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
+		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::EggLaid(100, 4, 5)));
+
+		assert_eq!(KittiesModule::hatch_queue(4).into_inner(), vec![(100, 2), (100, 3)]);
+		assert_eq!(KittiesModule::hatch_queue(5).into_inner(), vec![(100, 4)]);
+
+		KittiesModule::on_initialize(4);
+		assert!(Kitties::<Test>::contains_key(100, 2));
+		assert!(Kitties::<Test>::contains_key(100, 3));
+		assert!(!Kitties::<Test>::contains_key(100, 4));
+
+		KittiesModule::on_initialize(5);
+		assert!(Kitties::<Test>::contains_key(100, 4));
+	});
+}
+
+#[test]
+fn should_enforce_breeding_cooldown() {
+	new_test_ext().execute_with(|| {
+		KittiesModule::insert_kitty(&100, 0, &Kitty([0; 16]));
+		KittiesModule::insert_kitty(&100, 1, &Kitty([1; 16]));
+		NextKittyId::<Test>::put(2);
+		KittiesModule::set_market_param(ParamChange::BreedingCooldown(10));
+
+		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
+		assert_eq!(KittiesModule::ready_to_breed_at(0), 11);
+		assert_eq!(KittiesModule::ready_to_breed_at(1), 11);
+
+		assert_noop!(
+			KittiesModule::breed(Origin::signed(100), 0, 1),
+			Error::<Test>::KittyOnCooldown
+		);
+		assert_eq!(
+			check_kitty_call(100, crate::Call::breed { kitty1_id: 1, kitty2_id: 0 }),
+			Err(InvalidKittyCall::OnCooldown.into())
+		);
+
+		System::set_block_number(11);
+		assert_ok!(KittiesModule::breed(Origin::signed(100), 0, 1));
+	});
+}
+
 #[test]
 fn should_not_breed_when_kitty_not_found() {
 	new_test_ext().execute_with(|| {
@@ -139,6 +310,129 @@ fn should_not_transfer_to_self() {
 	});
 }
 
+#[test]
+fn should_index_kitty_owners() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_eq!(KittiesModule::owner_of(0), Some(100));
+		assert_eq!(KittiesModule::kitty_count(100), 2);
+
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 200, 0));
+
+		assert_eq!(KittiesModule::owner_of(0), Some(200));
+		assert_eq!(KittiesModule::kitty_count(100), 1);
+		assert_eq!(KittiesModule::kitty_count(200), 1);
+
+		assert_ok!(KittiesModule::force_burn(Origin::root(), 100, 1));
+
+		assert_eq!(KittiesModule::owner_of(1), None);
+		assert_eq!(KittyCount::<Test>::contains_key(100), false);
+	});
+}
+
+#[test]
+fn should_record_owned_since() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_eq!(KittiesModule::owned_since(0), 1);
+
+		System::set_block_number(5);
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 200, 0));
+		assert_eq!(KittiesModule::owned_since(0), 5);
+
+		assert_ok!(KittiesModule::force_burn(Origin::root(), 200, 0));
+		assert_eq!(OwnedSince::<Test>::contains_key(0), false);
+	});
+}
+
+#[test]
+fn should_transfer_approved_kitty() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_ok!(KittiesModule::approve(Origin::signed(100), Some(300), 0));
+		assert_eq!(KittiesModule::approvals(0), Some(300));
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyApproved(
+			100,
+			Some(300),
+			0,
+		)));
+
+		assert_ok!(KittiesModule::transfer_from(Origin::signed(300), 100, 200, 0));
+
+		assert_eq!(KittiesModule::owner_of(0), Some(200));
+		assert_eq!(Kitties::<Test>::contains_key(200, 0), true);
+
+		// the approval is cleared when the kitty changes hands
+		assert_eq!(KittiesModule::approvals(0), None);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyTransferred(
+			100, 200, 0,
+		)));
+
+		// the owner can always use `transfer_from`
+		assert_ok!(KittiesModule::transfer_from(Origin::signed(200), 200, 100, 0));
+		assert_eq!(KittiesModule::owner_of(0), Some(100));
+	});
+}
+
+#[test]
+fn should_not_transfer_from_when_not_approved() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_noop!(
+			KittiesModule::transfer_from(Origin::signed(300), 100, 300, 0),
+			Error::<Test>::NotApproved
+		);
+		assert_noop!(
+			KittiesModule::transfer_from(Origin::signed(300), 200, 300, 0),
+			Error::<Test>::InvalidKittyId
+		);
+		assert_noop!(
+			KittiesModule::approve(Origin::signed(300), Some(300), 0),
+			Error::<Test>::NotOwner
+		);
+
+		// removing the approval revokes it
+		assert_ok!(KittiesModule::approve(Origin::signed(100), Some(300), 0));
+		assert_ok!(KittiesModule::approve(Origin::signed(100), None, 0));
+
+		assert_noop!(
+			KittiesModule::transfer_from(Origin::signed(300), 100, 300, 0),
+			Error::<Test>::NotApproved
+		);
+	});
+}
+
+#[test]
+fn should_migrate_to_owner_index() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<KittiesModule>();
+
+		Kitties::<Test>::insert(100, 0, Kitty([0; 16]));
+		Kitties::<Test>::insert(100, 1, Kitty([1; 16]));
+		Kitties::<Test>::insert(200, 2, Kitty([2; 16]));
+
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!(KittiesModule::owner_of(0), Some(100));
+		assert_eq!(KittiesModule::owner_of(1), Some(100));
+		assert_eq!(KittiesModule::owner_of(2), Some(200));
+		assert_eq!(KittiesModule::kitty_count(100), 2);
+		assert_eq!(KittiesModule::kitty_count(200), 1);
+		assert_eq!(KittiesModule::on_chain_storage_version(), 1);
+
+		// running it again changes nothing
+		KittiesModule::on_runtime_upgrade();
+
+		assert_eq!(KittiesModule::kitty_count(100), 2);
+	});
+}
+
 #[test]
 fn should_set_kitty_price() {
 	new_test_ext().execute_with(|| {
@@ -199,6 +493,25 @@ fn should_buy() {
 	});
 }
 
+#[test]
+fn should_charge_sale_fee() {
+	new_test_ext().execute_with(|| {
+		KittiesModule::set_market_param(ParamChange::SaleFee(Permill::from_percent(10)));
+		System::assert_last_event(Event::KittiesModule(crate::Event::MarketParamChanged(
+			ParamChange::SaleFee(Permill::from_percent(10)),
+		)));
+
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(333)));
+		assert_ok!(KittiesModule::buy(Origin::signed(200), 100, 0, 333));
+
+		// the buyer pays the price, of which the pallet account gets 10%
+		assert_eq!(Balances::free_balance(100), 300);
+		assert_eq!(Balances::free_balance(200), 167);
+		assert_eq!(Balances::free_balance(KittiesModule::account_id()), 33);
+	});
+}
+
 #[test]
 fn should_fail_buy_from_self() {
 	new_test_ext().execute_with(|| {
@@ -254,3 +567,1081 @@ fn should_fail_buy_when_insufficient_balance() {
 		);
 	});
 }
+
+#[test]
+fn should_force_transfer_kitty() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(10)));
+
+		assert_ok!(KittiesModule::force_transfer(Origin::root(), 100, 200, 0));
+
+		assert_eq!(Kitties::<Test>::contains_key(100, 0), false);
+		assert_eq!(Kitties::<Test>::contains_key(200, 0), true);
+		assert_eq!(KittyPrices::<Test>::contains_key(0), false);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyForceTransferred(
+			100, 200, 0,
+		)));
+	});
+}
+
+#[test]
+fn should_force_delist_kitty() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(10)));
+
+		assert_ok!(KittiesModule::force_delist(Origin::root(), 0));
+
+		assert_eq!(KittyPrices::<Test>::contains_key(0), false);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyForceDelisted(0)));
+
+		assert_noop!(KittiesModule::force_delist(Origin::root(), 0), Error::<Test>::NotForSale);
+	});
+}
+
+#[test]
+fn should_force_burn_kitty() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(10)));
+
+		assert_ok!(KittiesModule::force_burn(Origin::root(), 100, 0));
+
+		assert_eq!(Kitties::<Test>::contains_key(100, 0), false);
+		assert_eq!(KittyPrices::<Test>::contains_key(0), false);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyForceBurned(100, 0)));
+
+		assert_noop!(
+			KittiesModule::force_burn(Origin::root(), 100, 0),
+			Error::<Test>::InvalidKittyId
+		);
+	});
+}
+
+#[test]
+fn should_fail_force_calls_when_not_force_origin() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(10)));
+
+		assert_noop!(KittiesModule::force_transfer(Origin::signed(100), 100, 200, 0), BadOrigin);
+		assert_noop!(KittiesModule::force_delist(Origin::signed(100), 0), BadOrigin);
+		assert_noop!(KittiesModule::force_burn(Origin::signed(100), 100, 0), BadOrigin);
+	});
+}
+
+#[test]
+fn should_reject_paused_calls() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		MockRandom::set(H256::from([2; 32]));
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(10)));
+
+		let flags = PauseFlags { minting: true, breeding: true, trading: true };
+		assert_ok!(KittiesModule::set_paused(Origin::root(), flags));
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::PauseFlagsUpdated(flags)));
+
+		assert_noop!(KittiesModule::create(Origin::signed(100)), Error::<Test>::MintingPaused);
+		assert_noop!(
+			KittiesModule::breed(Origin::signed(100), 0, 1),
+			Error::<Test>::BreedingPaused
+		);
+		assert_noop!(
+			KittiesModule::transfer(Origin::signed(100), 200, 0),
+			Error::<Test>::TradingPaused
+		);
+		assert_noop!(
+			KittiesModule::set_price(Origin::signed(100), 0, None),
+			Error::<Test>::TradingPaused
+		);
+		assert_noop!(
+			KittiesModule::buy(Origin::signed(200), 100, 0, 10),
+			Error::<Test>::TradingPaused
+		);
+		assert_noop!(
+			KittiesModule::approve(Origin::signed(100), Some(300), 0),
+			Error::<Test>::TradingPaused
+		);
+
+		assert_noop!(
+			KittiesModule::schedule_action(
+				Origin::signed(100),
+				0,
+				ScheduledAction::SetPrice(None),
+				5
+			),
+			Error::<Test>::TradingPaused
+		);
+		assert_noop!(
+			KittiesModule::run_action(Origin::signed(100), 0),
+			Error::<Test>::TradingPaused
+		);
+
+		assert!(KittiesModule::is_call_paused(&crate::Call::create {}));
+		assert!(KittiesModule::is_call_paused(&crate::Call::claim { proof: vec![] }));
+		assert!(KittiesModule::is_call_paused(&crate::Call::reveal { secret: [0; 32] }));
+		assert!(KittiesModule::is_call_paused(&crate::Call::buy {
+			seller: 100,
+			kitty_id: 0,
+			bid_price: 10
+		}));
+		assert!(KittiesModule::is_call_paused(&crate::Call::approve {
+			approved: Some(300),
+			kitty_id: 0
+		}));
+		assert!(KittiesModule::is_call_paused(&crate::Call::schedule_action {
+			kitty_id: 0,
+			action: ScheduledAction::Transfer(200),
+			when: 5
+		}));
+		assert!(KittiesModule::is_call_paused(&crate::Call::run_action { kitty_id: 0 }));
+
+		// the force origin is not affected by the pause flags
+		assert_ok!(KittiesModule::force_delist(Origin::root(), 0));
+	});
+}
+
+#[test]
+fn should_pause_trading_only() {
+	new_test_ext().execute_with(|| {
+		let flags = PauseFlags { minting: false, breeding: false, trading: true };
+		assert_ok!(KittiesModule::set_paused(Origin::root(), flags));
+
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert!(!KittiesModule::is_call_paused(&crate::Call::create {}));
+		assert!(!KittiesModule::is_call_paused(&crate::Call::reveal { secret: [0; 32] }));
+
+		assert_noop!(
+			KittiesModule::transfer(Origin::signed(100), 200, 0),
+			Error::<Test>::TradingPaused
+		);
+
+		assert_ok!(KittiesModule::set_paused(Origin::root(), Default::default()));
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 200, 0));
+	});
+}
+
+#[test]
+fn should_fail_set_paused_when_not_pause_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(KittiesModule::set_paused(Origin::signed(100), Default::default()), BadOrigin);
+	});
+}
+
+fn commitment_of(who: u64, secret: [u8; 32]) -> H256 {
+	BlakeTwo256::hash_of(&(who, secret))
+}
+
+#[test]
+fn should_commit_and_reveal_create() {
+	new_test_ext().execute_with(|| {
+		let secret = [7; 32];
+
+		assert_ok!(KittiesModule::commit(
+			Origin::signed(300),
+			CommitAction::Create,
+			commitment_of(300, secret)
+		));
+
+		assert_eq!(Balances::reserved_balance(300), 50);
+		System::assert_last_event(Event::KittiesModule(crate::Event::DnaCommitted(
+			300,
+			CommitAction::Create,
+		)));
+
+		assert_noop!(
+			KittiesModule::reveal(Origin::signed(300), secret),
+			Error::<Test>::RevealTooEarly
+		);
+
+		System::set_block_number(6);
+
+		assert_noop!(
+			KittiesModule::reveal(Origin::signed(300), [8; 32]),
+			Error::<Test>::InvalidReveal
+		);
+
+		assert_ok!(KittiesModule::reveal(Origin::signed(300), secret));
+
+		let kitty =
+			Kitty([216, 253, 224, 113, 128, 211, 211, 228, 251, 130, 84, 201, 80, 169, 60, 183]);
+
+		assert_eq!(KittiesModule::kitties(300, 0), Some(kitty.clone()));
+		assert_eq!(KittiesModule::commitments(300), None);
+		assert_eq!(Balances::reserved_balance(300), 0);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyCreated(300, 0, kitty)));
+	});
+}
+
+#[test]
+fn should_commit_and_reveal_breed() {
+	new_test_ext().execute_with(|| {
+		let secret = [7; 32];
+
+		Kitties::<Test>::insert(300, 0, Kitty([0; 16]));
+		Kitties::<Test>::insert(300, 1, Kitty([255; 16]));
+		NextKittyId::<Test>::put(2);
+
+		assert_noop!(
+			KittiesModule::commit(
+				Origin::signed(300),
+				CommitAction::Breed(0, 0),
+				commitment_of(300, secret)
+			),
+			Error::<Test>::SameGender
+		);
+
+		assert_ok!(KittiesModule::commit(
+			Origin::signed(300),
+			CommitAction::Breed(0, 1),
+			commitment_of(300, secret)
+		));
+
+		System::set_block_number(6);
+		MockRandom::set(H256::from([2; 32]));
+
+		assert_ok!(KittiesModule::reveal(Origin::signed(300), secret));
+
+		let kitty =
+			Kitty([137, 239, 199, 26, 47, 78, 21, 208, 108, 67, 109, 103, 207, 224, 143, 115]);
+
+		// the bred kitty hatches like those of `breed`
+		assert_eq!(KittiesModule::kitties(300, 2), None);
+		assert_eq!(
+			KittiesModule::eggs(300, 2).map(|egg| egg.kind),
+			Some(EggKind::Revealed { parents: (0, 1), dna: kitty.dna() })
+		);
+		System::assert_last_event(Event::KittiesModule(crate::Event::EggLaid(300, 2, 9)));
+
+		KittiesModule::on_initialize(9);
+
+		assert_eq!(KittiesModule::kitties(300, 2), Some(kitty.clone()));
+		assert_eq!(KittiesModule::parents(2), Some((0, 1)));
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittyCreatedByBreeding(
+			300, 2, kitty,
+		)));
+	});
+}
+
+#[test]
+fn should_not_commit_twice() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::commit(
+			Origin::signed(300),
+			CommitAction::Create,
+			commitment_of(300, [7; 32])
+		));
+
+		assert_noop!(
+			KittiesModule::commit(
+				Origin::signed(300),
+				CommitAction::Create,
+				commitment_of(300, [8; 32])
+			),
+			Error::<Test>::CommitmentExists
+		);
+	});
+}
+
+#[test]
+fn should_reap_expired_commitment() {
+	new_test_ext().execute_with(|| {
+		let secret = [7; 32];
+
+		assert_ok!(KittiesModule::commit(
+			Origin::signed(300),
+			CommitAction::Create,
+			commitment_of(300, secret)
+		));
+
+		System::set_block_number(16);
+
+		assert_noop!(
+			KittiesModule::reap_commitment(Origin::signed(200), 300),
+			Error::<Test>::CommitmentNotExpired
+		);
+
+		System::set_block_number(17);
+
+		assert_noop!(
+			KittiesModule::reveal(Origin::signed(300), secret),
+			Error::<Test>::CommitmentExpired
+		);
+
+		assert_ok!(KittiesModule::reap_commitment(Origin::signed(200), 300));
+
+		assert_eq!(KittiesModule::commitments(300), None);
+		assert_eq!(Balances::reserved_balance(300), 0);
+		assert_eq!(Balances::free_balance(300), 450);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::CommitmentReaped(300, 50)));
+	});
+}
+
+#[test]
+fn should_pay_bonding_curve_mint_price() {
+	new_test_ext().execute_with(|| {
+		MintBasePrice::set(10);
+		MintPriceIncrement::set(5);
+
+		assert_eq!(KittiesModule::mint_price(), 10);
+		assert_ok!(KittiesModule::create(Origin::signed(200)));
+
+		assert_eq!(KittiesModule::mint_price(), 15);
+		assert_ok!(KittiesModule::create(Origin::signed(200)));
+
+		assert_eq!(KittiesModule::gen0_minted(), 2);
+		assert_eq!(KittiesModule::mint_price(), 20);
+		assert_eq!(Balances::free_balance(200), 475);
+		assert_eq!(Balances::free_balance(KittiesModule::account_id()), 25);
+	});
+}
+
+#[test]
+fn should_fail_create_when_gen0_supply_exhausted() {
+	new_test_ext().execute_with(|| {
+		MaxGen0Supply::set(1);
+
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_noop!(
+			KittiesModule::create(Origin::signed(100)),
+			Error::<Test>::Gen0SupplyExhausted
+		);
+		assert_noop!(
+			KittiesModule::commit(
+				Origin::signed(300),
+				CommitAction::Create,
+				commitment_of(300, [7; 32])
+			),
+			Error::<Test>::Gen0SupplyExhausted
+		);
+	});
+}
+
+#[test]
+fn should_fail_create_when_insufficient_balance() {
+	new_test_ext().execute_with(|| {
+		MintBasePrice::set(10);
+
+		assert_noop!(
+			KittiesModule::create(Origin::signed(100)),
+			pallet_balances::Error::<Test, _>::InsufficientBalance
+		);
+		assert_eq!(KittiesModule::next_kitty_id(), 0);
+	});
+}
+
+fn leaf_of(who: u64) -> [u8; 32] {
+	who.using_encoded(blake2_256)
+}
+
+/// Builds a 4-leaf Merkle tree of accounts #100, #200, #300 and #400.
+fn presale_tree() -> ([u8; 32], Vec<(u64, Vec<[u8; 32]>)>) {
+	let leaves: Vec<[u8; 32]> = [100, 200, 300, 400].iter().map(|who| leaf_of(*who)).collect();
+
+	let left = KittiesModule::merkle_root(leaves[0], &[leaves[1]]);
+	let right = KittiesModule::merkle_root(leaves[2], &[leaves[3]]);
+	let root = KittiesModule::merkle_root(left, &[right]);
+
+	let proofs = vec![
+		(100, vec![leaves[1], right]),
+		(200, vec![leaves[0], right]),
+		(300, vec![leaves[3], left]),
+		(400, vec![leaves[2], left]),
+	];
+
+	(root, proofs)
+}
+
+#[test]
+fn should_verify_merkle_proofs() {
+	let (root, proofs) = presale_tree();
+
+	for (who, proof) in proofs.iter() {
+		assert!(KittiesModule::verify_merkle_proof(root, leaf_of(*who), proof));
+	}
+
+	assert!(!KittiesModule::verify_merkle_proof(root, leaf_of(500), &proofs[0].1));
+	assert!(!KittiesModule::verify_merkle_proof(root, leaf_of(100), &proofs[1].1));
+}
+
+#[test]
+fn should_claim_during_presale() {
+	new_test_ext().execute_with(|| {
+		let (root, proofs) = presale_tree();
+
+		assert_ok!(KittiesModule::set_presale(Origin::root(), root, 5, 10));
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::PresaleScheduled(
+			root, 5, 10,
+		)));
+
+		// the presale has not started yet
+		assert_noop!(
+			KittiesModule::claim(Origin::signed(200), proofs[1].1.clone()),
+			Error::<Test>::PresaleNotActive
+		);
+		assert_noop!(KittiesModule::create(Origin::signed(200)), Error::<Test>::PublicMintNotOpen);
+
+		System::set_block_number(5);
+
+		assert_ok!(KittiesModule::claim(Origin::signed(200), proofs[1].1.clone()));
+
+		assert!(KittiesModule::claimed(200));
+		assert!(Kitties::<Test>::contains_key(200, 0));
+
+		assert_noop!(
+			KittiesModule::claim(Origin::signed(200), proofs[1].1.clone()),
+			Error::<Test>::AlreadyClaimed
+		);
+
+		// wrong proof for the sender, and an account outside of the allowlist
+		assert_noop!(
+			KittiesModule::claim(Origin::signed(300), proofs[1].1.clone()),
+			Error::<Test>::InvalidMerkleProof
+		);
+		assert_noop!(
+			KittiesModule::claim(Origin::signed(500), proofs[0].1.clone()),
+			Error::<Test>::InvalidMerkleProof
+		);
+
+		assert_noop!(KittiesModule::create(Origin::signed(200)), Error::<Test>::PublicMintNotOpen);
+
+		// after the presale, `create` opens to everyone
+		System::set_block_number(10);
+
+		assert_noop!(
+			KittiesModule::claim(Origin::signed(300), proofs[2].1.clone()),
+			Error::<Test>::PresaleNotActive
+		);
+		assert_ok!(KittiesModule::create(Origin::signed(500)));
+	});
+}
+
+#[test]
+fn should_fail_set_presale_when_invalid() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			KittiesModule::set_presale(Origin::root(), [0; 32], 10, 10),
+			Error::<Test>::InvalidPresaleSchedule
+		);
+		assert_noop!(KittiesModule::set_presale(Origin::signed(100), [0; 32], 5, 10), BadOrigin);
+	});
+}
+
+fn sign_order(signer: u64, order: &SellOrderOf<Test>) -> TestSignature {
+	TestSignature(signer, KittiesModule::order_signing_payload(order))
+}
+
+#[test]
+fn should_fill_signed_order() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::set_price(Origin::signed(100), 0, Some(300)));
+
+		let order = SellOrder { kitty_id: 0, price: 100, expiry: 10, nonce: 0 };
+		let signature = sign_order(100, &order);
+
+		assert_ok!(KittiesModule::fill_order(
+			Origin::signed(200),
+			100,
+			order.clone(),
+			signature.clone()
+		));
+
+		assert_eq!(Kitties::<Test>::contains_key(200, 0), true);
+		assert_eq!(KittyPrices::<Test>::contains_key(0), false);
+		assert_eq!(Balances::free_balance(100), 100);
+		assert_eq!(Balances::free_balance(200), 400);
+
+		System::assert_last_event(Event::KittiesModule(crate::Event::KittySold(100, 200, 0, 100)));
+
+		// the same order can't be filled twice, even if the seller owns the kitty again
+		assert_ok!(KittiesModule::transfer(Origin::signed(200), 100, 0));
+		assert_noop!(
+			KittiesModule::fill_order(Origin::signed(200), 100, order, signature),
+			Error::<Test>::OrderAlreadyFilled
+		);
+	});
+}
+
+#[test]
+fn should_fail_fill_order_when_invalid() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		let order = SellOrder { kitty_id: 0, price: 100, expiry: 10, nonce: 0 };
+
+		assert_noop!(
+			KittiesModule::fill_order(
+				Origin::signed(200),
+				100,
+				order.clone(),
+				sign_order(101, &order)
+			),
+			Error::<Test>::InvalidSignature
+		);
+
+		let other = SellOrder { kitty_id: 0, price: 1, expiry: 10, nonce: 0 };
+		assert_noop!(
+			KittiesModule::fill_order(Origin::signed(200), 100, other, sign_order(100, &order)),
+			Error::<Test>::InvalidSignature
+		);
+
+		assert_noop!(
+			KittiesModule::fill_order(
+				Origin::signed(100),
+				100,
+				order.clone(),
+				sign_order(100, &order)
+			),
+			Error::<Test>::BuyerIsSeller
+		);
+
+		let not_owned = SellOrder { kitty_id: 1, price: 100, expiry: 10, nonce: 0 };
+		assert_noop!(
+			KittiesModule::fill_order(
+				Origin::signed(200),
+				100,
+				not_owned.clone(),
+				sign_order(100, &not_owned)
+			),
+			Error::<Test>::InvalidKittyId
+		);
+
+		System::set_block_number(11);
+
+		assert_noop!(
+			KittiesModule::fill_order(
+				Origin::signed(200),
+				100,
+				order.clone(),
+				sign_order(100, &order)
+			),
+			Error::<Test>::OrderExpired
+		);
+	});
+}
+
+#[test]
+fn should_bind_orders_to_genesis_hash() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		let order = SellOrder { kitty_id: 0, price: 100, expiry: 10, nonce: 0 };
+		let signature = sign_order(100, &order);
+
+		// the same order on a chain with another genesis
+		frame_system::BlockHash::<Test>::insert(0, H256::repeat_byte(1));
+
+		assert_noop!(
+			KittiesModule::fill_order(Origin::signed(200), 100, order, signature),
+			Error::<Test>::InvalidSignature
+		);
+	});
+}
+
+#[test]
+fn should_prune_expired_filled_orders() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		let order = SellOrder { kitty_id: 0, price: 100, expiry: 10, nonce: 0 };
+		let order_hash = BlakeTwo256::hash_of(&(100u64, &order));
+		assert_ok!(KittiesModule::fill_order(
+			Origin::signed(200),
+			100,
+			order.clone(),
+			sign_order(100, &order)
+		));
+		assert!(FilledOrders::<Test>::contains_key(10, order_hash));
+
+		// the order can still be filled at its expiry, and there is no weight left to prune
+		KittiesModule::on_idle(10, u64::MAX);
+		assert!(FilledOrders::<Test>::contains_key(10, order_hash));
+		assert_eq!(KittiesModule::on_idle(11, 0), 0);
+		assert!(FilledOrders::<Test>::contains_key(10, order_hash));
+
+		KittiesModule::on_idle(11, u64::MAX);
+		assert!(!FilledOrders::<Test>::contains_key(10, order_hash));
+		assert_eq!(KittiesModule::filled_orders_pruned_to(), 11);
+	});
+}
+
+#[test]
+fn should_cancel_orders() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		let order = SellOrder { kitty_id: 0, price: 100, expiry: 10, nonce: 0 };
+
+		assert_ok!(KittiesModule::cancel_orders(Origin::signed(100)));
+
+		assert_eq!(KittiesModule::order_nonces(100), 1);
+		System::assert_last_event(Event::KittiesModule(crate::Event::OrdersCancelled(100, 1)));
+
+		assert_noop!(
+			KittiesModule::fill_order(
+				Origin::signed(200),
+				100,
+				order.clone(),
+				sign_order(100, &order)
+			),
+			Error::<Test>::InvalidOrderNonce
+		);
+
+		let order = SellOrder { nonce: 1, ..order };
+		assert_ok!(KittiesModule::fill_order(
+			Origin::signed(200),
+			100,
+			order.clone(),
+			sign_order(100, &order)
+		));
+	});
+}
+
+fn validate_create(who: u64, tip: u64) -> TransactionValidity {
+	let call = Call::KittiesModule(crate::Call::create {});
+	let info = call.get_dispatch_info();
+
+	ChargeSponsoredTransactionPayment::<Test>::from(tip).validate(&who, &call, &info, 10)
+}
+
+fn dispatch_create(who: u64) {
+	let call = Call::KittiesModule(crate::Call::create {});
+	let info = call.get_dispatch_info();
+
+	let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+		.pre_dispatch(&who, &call, &info, 10)
+		.unwrap();
+	assert_ok!(call.dispatch(Origin::signed(who)));
+	assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+		Some(pre),
+		&info,
+		&PostDispatchInfo { actual_weight: None, pays_fee: Default::default() },
+		10,
+		&Ok(())
+	));
+}
+
+#[test]
+fn should_sponsor_first_mint() {
+	new_test_ext().execute_with(|| {
+		let sponsor = KittiesModule::sponsor_account();
+		Balances::make_free_balance_be(&sponsor, 1_000_000_000);
+
+		assert_ok!(validate_create(100, 0));
+
+		dispatch_create(100);
+
+		assert!(Kitties::<Test>::contains_key(100, 0));
+		assert!(Balances::free_balance(sponsor) < 1_000_000_000);
+		assert_eq!(Balances::free_balance(100), 0);
+		assert_eq!(KittiesModule::sponsored_mints(100), 1);
+
+		// the sender now pays its own fees, and has no funds for them
+		assert_eq!(validate_create(100, 0), InvalidTransaction::Payment.into());
+	});
+}
+
+#[test]
+fn should_not_sponsor_when_ineligible() {
+	new_test_ext().execute_with(|| {
+		let sponsor = KittiesModule::sponsor_account();
+		Balances::make_free_balance_be(&sponsor, 1_000_000_000);
+
+		// a tip means the sender pays
+		assert_eq!(validate_create(101, 1), InvalidTransaction::Payment.into());
+
+		// only `create` is sponsored
+		let call = Call::KittiesModule(crate::Call::cancel_orders {});
+		let info = call.get_dispatch_info();
+		assert_eq!(
+			ChargeSponsoredTransactionPayment::<Test>::from(0).validate(&101, &call, &info, 10),
+			InvalidTransaction::Payment.into()
+		);
+
+		// owners of a kitty are not new users, even once the kitty is gone
+		KittiesModule::insert_kitty(&101, 0, &Kitty([0; 16]));
+		assert!(!KittiesModule::can_sponsor_mint(&101));
+		assert_ok!(KittiesModule::transfer(Origin::signed(101), 200, 0));
+		assert!(!KittiesModule::can_sponsor_mint(&101));
+
+		// the sender must be able to pay the mint price and stay alive
+		MintBasePrice::set(10);
+		assert!(!KittiesModule::can_sponsor_mint(&102));
+		Balances::make_free_balance_be(&102, 10);
+		assert!(!KittiesModule::can_sponsor_mint(&102));
+		Balances::make_free_balance_be(&102, 11);
+		assert!(KittiesModule::can_sponsor_mint(&102));
+
+		// `create` must be allowed
+		MaxGen0Supply::set(0);
+		assert!(!KittiesModule::can_sponsor_mint(&102));
+		MaxGen0Supply::set(100);
+
+		let flags = PauseFlags { minting: true, breeding: false, trading: false };
+		assert_ok!(KittiesModule::set_paused(Origin::root(), flags));
+		assert!(!KittiesModule::can_sponsor_mint(&102));
+		assert_ok!(KittiesModule::set_paused(Origin::root(), Default::default()));
+
+		assert_ok!(KittiesModule::set_presale(Origin::root(), [0; 32], 5, 10));
+		assert!(!KittiesModule::can_sponsor_mint(&102));
+	});
+}
+
+#[test]
+fn should_not_count_failed_sponsored_mint() {
+	new_test_ext().execute_with(|| {
+		let sponsor = KittiesModule::sponsor_account();
+		Balances::make_free_balance_be(&sponsor, 1_000_000_000);
+
+		let call = Call::KittiesModule(crate::Call::create {});
+		let info = call.get_dispatch_info();
+
+		let pre = ChargeSponsoredTransactionPayment::<Test>::from(0)
+			.pre_dispatch(&100, &call, &info, 10)
+			.unwrap();
+		assert_ok!(ChargeSponsoredTransactionPayment::<Test>::post_dispatch(
+			Some(pre),
+			&info,
+			&PostDispatchInfo { actual_weight: None, pays_fee: Default::default() },
+			10,
+			&Err(Error::<Test>::MintingPaused.into())
+		));
+
+		assert_eq!(KittiesModule::sponsored_mints(100), 0);
+		assert_eq!(KittiesModule::sponsored_in_block(), (0, 0));
+		assert_ok!(validate_create(100, 0));
+	});
+}
+
+#[test]
+fn should_limit_sponsored_mints_per_account() {
+	new_test_ext().execute_with(|| {
+		let sponsor = KittiesModule::sponsor_account();
+		Balances::make_free_balance_be(&sponsor, 1_000_000_000);
+		MaxSponsoredPerAccount::set(2);
+
+		dispatch_create(100);
+		dispatch_create(100);
+
+		assert_eq!(KittiesModule::sponsored_mints(100), 2);
+		assert!(!KittiesModule::can_sponsor_mint(&100));
+
+		// a kitty from anywhere else ends the sponsoring
+		System::set_block_number(2);
+		dispatch_create(101);
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 101, 0));
+
+		assert!(!KittiesModule::can_sponsor_mint(&101));
+	});
+}
+
+#[test]
+fn should_limit_sponsored_mints_per_block() {
+	new_test_ext().execute_with(|| {
+		let sponsor = KittiesModule::sponsor_account();
+		Balances::make_free_balance_be(&sponsor, 1_000_000_000);
+
+		dispatch_create(101);
+		dispatch_create(102);
+
+		assert_eq!(validate_create(103, 0), InvalidTransaction::Payment.into());
+
+		System::set_block_number(2);
+
+		assert_ok!(validate_create(103, 0));
+	});
+}
+
+#[test]
+fn should_fail_sponsored_mint_when_pool_empty() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(validate_create(100, 0), InvalidTransaction::Payment.into());
+	});
+}
+
+fn check_kitty_call(who: u64, call: crate::Call<Test>) -> TransactionValidity {
+	let call = Call::KittiesModule(call);
+	let info = call.get_dispatch_info();
+
+	CheckKittyCall::<Test>::new().validate(&who, &call, &info, 10)
+}
+
+#[test]
+fn should_validate_kitty_calls() {
+	new_test_ext().execute_with(|| {
+		Kitties::<Test>::insert(100, 0, Kitty([0; 16]));
+		Kitties::<Test>::insert(100, 1, Kitty([1; 16]));
+		KittyPrices::<Test>::insert(0, 333);
+
+		assert_ok!(check_kitty_call(100, crate::Call::breed { kitty1_id: 0, kitty2_id: 1 }));
+		assert_ok!(check_kitty_call(100, crate::Call::transfer { to: 200, kitty_id: 1 }));
+		assert_ok!(check_kitty_call(
+			200,
+			crate::Call::buy { seller: 100, kitty_id: 0, bid_price: 333 }
+		));
+		assert_ok!(check_kitty_call(100, crate::Call::create {}));
+	});
+}
+
+#[test]
+fn should_reject_invalid_kitty_calls() {
+	new_test_ext().execute_with(|| {
+		Kitties::<Test>::insert(100, 0, Kitty([0; 16]));
+		Kitties::<Test>::insert(100, 1, Kitty([2; 16]));
+		KittyPrices::<Test>::insert(0, 333);
+		KittyPrices::<Test>::insert(5, 333);
+
+		let invalid = |error: InvalidKittyCall| -> TransactionValidity { Err(error.into()) };
+
+		assert_eq!(
+			check_kitty_call(100, crate::Call::breed { kitty1_id: 0, kitty2_id: 1 }),
+			invalid(InvalidKittyCall::SameGender)
+		);
+		assert_eq!(
+			check_kitty_call(100, crate::Call::breed { kitty1_id: 0, kitty2_id: 0 }),
+			invalid(InvalidKittyCall::SameGender)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::breed { kitty1_id: 0, kitty2_id: 1 }),
+			invalid(InvalidKittyCall::NotOwner)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::transfer { to: 100, kitty_id: 0 }),
+			invalid(InvalidKittyCall::NotOwner)
+		);
+		assert_eq!(
+			check_kitty_call(100, crate::Call::buy { seller: 100, kitty_id: 0, bid_price: 333 }),
+			invalid(InvalidKittyCall::BuyerIsSeller)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::buy { seller: 100, kitty_id: 1, bid_price: 333 }),
+			invalid(InvalidKittyCall::NotForSale)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::buy { seller: 100, kitty_id: 0, bid_price: 332 }),
+			invalid(InvalidKittyCall::BidPriceTooLow)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::buy { seller: 300, kitty_id: 5, bid_price: 333 }),
+			invalid(InvalidKittyCall::NotOwner)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::transfer_from { from: 300, to: 200, kitty_id: 0 }),
+			invalid(InvalidKittyCall::NotOwner)
+		);
+		assert_eq!(
+			check_kitty_call(200, crate::Call::transfer_from { from: 100, to: 200, kitty_id: 0 }),
+			invalid(InvalidKittyCall::NotApproved)
+		);
+
+		assert_eq!(invalid(InvalidKittyCall::NotOwner), InvalidTransaction::Custom(1).into());
+	});
+}
+
+/// Run the scheduler at block `n`.
+fn run_scheduler(n: u64) {
+	System::set_block_number(n);
+	Scheduler::on_initialize(n);
+}
+
+#[test]
+fn should_run_scheduled_actions() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&100, 100);
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			0,
+			ScheduledAction::Transfer(200),
+			5
+		));
+		System::assert_last_event(Event::KittiesModule(crate::Event::ActionScheduled(
+			100,
+			0,
+			ScheduledAction::Transfer(200),
+			5,
+		)));
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			1,
+			ScheduledAction::SetPrice(Some(300)),
+			5
+		));
+
+		// a deposit is reserved for each action
+		assert_eq!(Balances::reserved_balance(100), 20);
+
+		run_scheduler(4);
+		assert_eq!(KittiesModule::owner_of(0), Some(100));
+		assert_eq!(KittiesModule::kitty_prices(1), None);
+
+		run_scheduler(5);
+		assert_eq!(KittiesModule::owner_of(0), Some(200));
+		assert_eq!(KittiesModule::kitty_prices(1), Some(300));
+		assert_eq!(Balances::reserved_balance(100), 0);
+		assert_eq!(KittiesModule::scheduled_actions(100, 0), None);
+		System::assert_has_event(Event::KittiesModule(crate::Event::ActionRun(100, 1, Ok(()))));
+	});
+}
+
+#[test]
+fn scheduled_actions_should_recheck_ownership() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&100, 100);
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			0,
+			ScheduledAction::Transfer(300),
+			5
+		));
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			1,
+			ScheduledAction::SetPrice(Some(300)),
+			5
+		));
+
+		// both kitties change hands before the actions run
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 200, 0));
+		assert_ok!(KittiesModule::transfer(Origin::signed(100), 200, 1));
+
+		run_scheduler(5);
+		assert_eq!(KittiesModule::owner_of(0), Some(200));
+		assert_eq!(KittiesModule::kitty_prices(1), None);
+
+		// the deposits are returned even though the actions failed
+		assert_eq!(Balances::reserved_balance(100), 0);
+		System::assert_has_event(Event::KittiesModule(crate::Event::ActionRun(
+			100,
+			0,
+			Err(Error::<Test>::InvalidKittyId.into()),
+		)));
+	});
+}
+
+#[test]
+fn should_cancel_scheduled_action() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&100, 100);
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			0,
+			ScheduledAction::Transfer(200),
+			5
+		));
+		assert_noop!(
+			KittiesModule::cancel_action(Origin::signed(200), 0),
+			Error::<Test>::NotScheduled
+		);
+
+		assert_eq!(Balances::reserved_balance(100), 10);
+		assert_ok!(KittiesModule::cancel_action(Origin::signed(100), 0));
+		System::assert_last_event(Event::KittiesModule(crate::Event::ActionCancelled(100, 0)));
+		assert_eq!(Balances::reserved_balance(100), 0);
+
+		run_scheduler(5);
+		assert_eq!(KittiesModule::owner_of(0), Some(100));
+
+		assert_noop!(
+			KittiesModule::cancel_action(Origin::signed(100), 0),
+			Error::<Test>::NotScheduled
+		);
+	});
+}
+
+#[test]
+fn should_fail_schedule_action_when_invalid() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		// #100 cannot pay the deposit
+		assert_noop!(
+			KittiesModule::schedule_action(
+				Origin::signed(100),
+				0,
+				ScheduledAction::Transfer(200),
+				5
+			),
+			pallet_balances::Error::<Test, _>::InsufficientBalance
+		);
+
+		Balances::make_free_balance_be(&100, 100);
+
+		assert_noop!(
+			KittiesModule::schedule_action(
+				Origin::signed(200),
+				0,
+				ScheduledAction::Transfer(200),
+				5
+			),
+			Error::<Test>::NotOwner
+		);
+		assert_noop!(
+			KittiesModule::schedule_action(
+				Origin::signed(100),
+				0,
+				ScheduledAction::Transfer(200),
+				1
+			),
+			Error::<Test>::ScheduleInPast
+		);
+
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			0,
+			ScheduledAction::Transfer(200),
+			5
+		));
+		assert_noop!(
+			KittiesModule::schedule_action(
+				Origin::signed(100),
+				0,
+				ScheduledAction::SetPrice(None),
+				6
+			),
+			Error::<Test>::AlreadyScheduled
+		);
+	});
+}
+
+#[test]
+fn should_not_run_action_before_its_block() {
+	new_test_ext().execute_with(|| {
+		Balances::make_free_balance_be(&100, 100);
+		assert_ok!(KittiesModule::create(Origin::signed(100)));
+
+		assert_noop!(
+			KittiesModule::run_action(Origin::signed(100), 0),
+			Error::<Test>::NotScheduled
+		);
+
+		assert_ok!(KittiesModule::schedule_action(
+			Origin::signed(100),
+			0,
+			ScheduledAction::Transfer(200),
+			5
+		));
+		assert_noop!(
+			KittiesModule::run_action(Origin::signed(100), 0),
+			Error::<Test>::ActionNotDue
+		);
+	});
+}
@@ -15,13 +15,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Autogenerated weights for pallet_kitties
+//! Estimated weights for pallet_kitties
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2022-03-10, STEPS: `1`, REPEAT: 1, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: None, DB CACHE: 1024
+//! THESE ARE NOT BENCHMARK RESULTS. The base weights of `create`, `breed`, `transfer`,
+//! `set_price` and `buy` come from a benchmark run of 2022-03-10 (STEPS: `1`, REPEAT: 1) that
+//! predates the storage those calls use now. Every other weight, and every read and write count,
+//! is a hand estimate. Replace this file with the output of the command below before relying on it.
 
-// Executed Command:
+// Command to generate the weights:
 // target/release/node-template
 // benchmark
 // --pallet
@@ -48,34 +49,62 @@ pub trait WeightInfo {
 	fn transfer() -> Weight;
 	fn set_price() -> Weight;
 	fn buy() -> Weight;
+	fn force_transfer() -> Weight;
+	fn force_delist() -> Weight;
+	fn force_burn() -> Weight;
+	fn set_paused() -> Weight;
+	fn commit() -> Weight;
+	fn reveal() -> Weight;
+	fn reap_commitment() -> Weight;
+	fn on_initialize(e: u32, ) -> Weight;
+	fn set_presale() -> Weight;
+	fn claim(p: u32, ) -> Weight;
+	fn fill_order() -> Weight;
+	fn cancel_orders() -> Weight;
+	fn approve() -> Weight;
+	fn transfer_from() -> Weight;
+	fn schedule_action() -> Weight;
+	fn cancel_action() -> Weight;
+	fn run_action() -> Weight;
 }
 
 /// Weights for pallet_kitties using the Substrate node and recommended hardware.
 pub struct SubstrateWeight<T>(PhantomData<T>);
 impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: Kitties Gen0Minted (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
 	// Storage: Kitties NextKittyId (r:1 w:1)
 	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
 	// Storage: unknown [0x3a65787472696e7369635f696e646578] (r:1 w:0)
 	// Storage: Kitties Kitties (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
 	fn create() -> Weight {
 		(49_362_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(3 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
 	}
-	// Storage: Kitties Kitties (r:2 w:1)
+	// Storage: Kitties Kitties (r:2 w:0)
 	// Storage: Kitties NextKittyId (r:1 w:1)
-	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
-	// Storage: unknown [0x3a65787472696e7369635f696e646578] (r:1 w:0)
+	// Storage: Kitties HatchQueue (r:1 w:1)
+	// Storage: Kitties Eggs (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
+	// Storage: Kitties ReadyToBreedAt (r:2 w:2)
 	fn breed() -> Weight {
 		(48_952_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(5 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
 	}
 	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
 	fn transfer() -> Weight {
 		(29_365_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(1 as Weight))
-			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
 	// Storage: Kitties Kitties (r:1 w:0)
 	// Storage: Kitties KittyPrices (r:1 w:1)
@@ -86,39 +115,233 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	}
 	// Storage: Kitties KittyPrices (r:1 w:1)
 	// Storage: Kitties Kitties (r:1 w:2)
-	// Storage: System Account (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
 	fn buy() -> Weight {
 		(92_504_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(10 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties KittyPrices (r:0 w:1)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn force_transfer() -> Weight {
+		(31_000_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Kitties KittyPrices (r:1 w:1)
+	fn force_delist() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:1)
+	// Storage: Kitties KittyPrices (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties ReadyToBreedAt (r:0 w:1)
+	// Storage: Kitties Parents (r:0 w:1)
+	fn force_burn() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Kitties Paused (r:0 w:1)
+	fn set_paused() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Commitments (r:1 w:1)
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	fn commit() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Kitties Commitments (r:1 w:1)
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: Kitties NextKittyId (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Kitties Kitties (r:2 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
+	// Storage: Kitties ReadyToBreedAt (r:2 w:2)
+	fn reveal() -> Weight {
+		(68_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+	}
+	// Storage: Kitties Commitments (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn reap_commitment() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Kitties HatchQueue (r:1 w:1)
+	// Storage: Kitties Eggs (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: Kitties Kitties (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Parents (r:0 w:1)
+	fn on_initialize(e: u32, ) -> Weight {
+		(3_410_000 as Weight)
+			// Standard Error: 21_000
+			.saturating_add((19_812_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(e as Weight)))
+	}
+	// Storage: Kitties Presale (r:0 w:1)
+	fn set_presale() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: Kitties Presale (r:1 w:0)
+	// Storage: Kitties Claimed (r:1 w:1)
+	// Storage: Kitties Gen0Minted (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: Kitties NextKittyId (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: unknown [0x3a65787472696e7369635f696e646578] (r:1 w:0)
+	// Storage: Kitties Kitties (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn claim(p: u32, ) -> Weight {
+		(84_205_000 as Weight)
+			// Standard Error: 4_000
+			.saturating_add((1_392_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+	}
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: Kitties OrderNonces (r:1 w:0)
+	// Storage: Kitties FilledOrders (r:1 w:1)
+	// Storage: Kitties KittyPrices (r:0 w:1)
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: System Account (r:2 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
+	fn fill_order() -> Weight {
+		(128_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(11 as Weight))
+	}
+	// Storage: Kitties OrderNonces (r:1 w:1)
+	fn cancel_orders() -> Weight {
+		(16_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:0)
+	// Storage: Kitties Approvals (r:0 w:1)
+	fn approve() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties Approvals (r:1 w:1)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn transfer_from() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:0)
+	// Storage: Kitties ScheduledActions (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Scheduler Lookup (r:1 w:1)
+	// Storage: Scheduler Agenda (r:1 w:1)
+	fn schedule_action() -> Weight {
+		(46_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: Kitties ScheduledActions (r:1 w:1)
+	// Storage: Scheduler Lookup (r:1 w:1)
+	// Storage: Scheduler Agenda (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn cancel_action() -> Weight {
+		(44_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
 			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
+	// Storage: Kitties ScheduledActions (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn run_action() -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(9 as Weight))
+	}
 }
 
 // For backwards compatibility and tests
 impl WeightInfo for () {
+	// Storage: Kitties Gen0Minted (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
 	// Storage: Kitties NextKittyId (r:1 w:1)
 	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
 	// Storage: unknown [0x3a65787472696e7369635f696e646578] (r:1 w:0)
 	// Storage: Kitties Kitties (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
 	fn create() -> Weight {
 		(49_362_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
 	}
-	// Storage: Kitties Kitties (r:2 w:1)
+	// Storage: Kitties Kitties (r:2 w:0)
 	// Storage: Kitties NextKittyId (r:1 w:1)
-	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
-	// Storage: unknown [0x3a65787472696e7369635f696e646578] (r:1 w:0)
+	// Storage: Kitties HatchQueue (r:1 w:1)
+	// Storage: Kitties Eggs (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
+	// Storage: Kitties ReadyToBreedAt (r:2 w:2)
 	fn breed() -> Weight {
 		(48_952_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
 	}
 	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
 	fn transfer() -> Weight {
 		(29_365_000 as Weight)
-			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
-			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
 	}
 	// Storage: Kitties Kitties (r:1 w:0)
 	// Storage: Kitties KittyPrices (r:1 w:1)
@@ -129,10 +352,193 @@ impl WeightInfo for () {
 	}
 	// Storage: Kitties KittyPrices (r:1 w:1)
 	// Storage: Kitties Kitties (r:1 w:2)
-	// Storage: System Account (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
 	fn buy() -> Weight {
 		(92_504_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(10 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties KittyPrices (r:0 w:1)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn force_transfer() -> Weight {
+		(31_000_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Kitties KittyPrices (r:1 w:1)
+	fn force_delist() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:1)
+	// Storage: Kitties KittyPrices (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties ReadyToBreedAt (r:0 w:1)
+	// Storage: Kitties Parents (r:0 w:1)
+	fn force_burn() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: Kitties Paused (r:0 w:1)
+	fn set_paused() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Commitments (r:1 w:1)
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: System Account (r:1 w:1)
+	fn commit() -> Weight {
+		(45_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Kitties Commitments (r:1 w:1)
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: Kitties NextKittyId (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Kitties Kitties (r:2 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
+	// Storage: Kitties ReadyToBreedAt (r:2 w:2)
+	fn reveal() -> Weight {
+		(68_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
+	// Storage: Kitties Commitments (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn reap_commitment() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Kitties HatchQueue (r:1 w:1)
+	// Storage: Kitties Eggs (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: Kitties Kitties (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Parents (r:0 w:1)
+	fn on_initialize(e: u32, ) -> Weight {
+		(3_410_000 as Weight)
+			// Standard Error: 21_000
+			.saturating_add((19_812_000 as Weight).saturating_mul(e as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((3 as Weight).saturating_mul(e as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(e as Weight)))
+	}
+	// Storage: Kitties Presale (r:0 w:1)
+	fn set_presale() -> Weight {
+		(17_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: Kitties Presale (r:1 w:0)
+	// Storage: Kitties Claimed (r:1 w:1)
+	// Storage: Kitties Gen0Minted (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: Kitties NextKittyId (r:1 w:1)
+	// Storage: RandomnessCollectiveFlip RandomMaterial (r:1 w:0)
+	// Storage: unknown [0x3a65787472696e7369635f696e646578] (r:1 w:0)
+	// Storage: Kitties Kitties (r:0 w:1)
+	// Storage: Kitties KittyCount (r:1 w:1)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn claim(p: u32, ) -> Weight {
+		(84_205_000 as Weight)
+			// Standard Error: 4_000
+			.saturating_add((1_392_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: Kitties OrderNonces (r:1 w:0)
+	// Storage: Kitties FilledOrders (r:1 w:1)
+	// Storage: Kitties KittyPrices (r:0 w:1)
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: System Account (r:2 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	// Storage: Kitties Params (r:1 w:0)
+	fn fill_order() -> Weight {
+		(128_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(11 as Weight))
+	}
+	// Storage: Kitties OrderNonces (r:1 w:1)
+	fn cancel_orders() -> Weight {
+		(16_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:0)
+	// Storage: Kitties Approvals (r:0 w:1)
+	fn approve() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Kitties Paused (r:1 w:0)
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties Approvals (r:1 w:1)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn transfer_from() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	// Storage: Kitties Kitties (r:1 w:0)
+	// Storage: Kitties ScheduledActions (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Scheduler Lookup (r:1 w:1)
+	// Storage: Scheduler Agenda (r:1 w:1)
+	fn schedule_action() -> Weight {
+		(46_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: Kitties ScheduledActions (r:1 w:1)
+	// Storage: Scheduler Lookup (r:1 w:1)
+	// Storage: Scheduler Agenda (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn cancel_action() -> Weight {
+		(44_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
 	}
+	// Storage: Kitties ScheduledActions (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: Kitties Kitties (r:1 w:2)
+	// Storage: Kitties KittyCount (r:2 w:2)
+	// Storage: Kitties Owners (r:0 w:1)
+	// Storage: Kitties Approvals (r:0 w:1)
+	// Storage: Kitties OwnedSince (r:0 w:1)
+	fn run_action() -> Weight {
+		(48_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(9 as Weight))
+	}
 }
@@ -0,0 +1,15 @@
+use super::*;
+
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_support::traits::EnsureOrigin;
+
+benchmarks! {
+	where_clause { where <T as Config>::Call: From<frame_system::Call<T>> }
+
+	dispatch_as_root {
+		let origin = T::RootOrigin::successful_origin();
+		let call: <T as Config>::Call = frame_system::Call::<T>::remark { remark: vec![] }.into();
+	}: _<T::Origin>(origin, Box::new(call))
+}
+
+impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,90 @@
+//! Lets `RootOrigin`, such as a council majority, dispatch calls as root.
+//!
+//! Calls like `frame_system::set_code` only accept the root origin. This pallet is how a
+//! collective reaches them without a sudo key: its motion dispatches `dispatch_as_root`, which
+//! checks the collective origin and dispatches the inner call as root.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use frame_support::{
+	dispatch::DispatchResult,
+	traits::UnfilteredDispatchable,
+	weights::{GetDispatchInfo, Pays},
+	Parameter,
+};
+use sp_runtime::DispatchErrorWithPostInfo;
+use sp_std::prelude::*;
+
+pub use pallet::*;
+pub use weights::WeightInfo;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod weights;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	// --- CONFIG ---
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
+		/// The calls that can be dispatched as root.
+		type Call: Parameter + UnfilteredDispatchable<Origin = Self::Origin> + GetDispatchInfo;
+		/// The origin which may dispatch calls as root.
+		type RootOrigin: EnsureOrigin<Self::Origin>;
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	// --- STORAGE ---
+	#[pallet::pallet]
+	pub struct Pallet<T>(_);
+
+	// --- EVENTS ---
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// A call was dispatched as root. \[result\]
+		DispatchedAsRoot(DispatchResult),
+	}
+
+	// --- CALLS ---
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Dispatch `call` with the root origin.
+		///
+		/// Like `pallet_sudo::sudo`, the outcome of `call` is reported in `DispatchedAsRoot`
+		/// rather than failing this call.
+		#[pallet::weight({
+			let dispatch_info = call.get_dispatch_info();
+			(
+				dispatch_info.weight.saturating_add(T::WeightInfo::dispatch_as_root()),
+				dispatch_info.class,
+			)
+		})]
+		pub fn dispatch_as_root(
+			origin: OriginFor<T>,
+			call: Box<<T as Config>::Call>,
+		) -> DispatchResultWithPostInfo {
+			T::RootOrigin::ensure_origin(origin)?;
+
+			let result = call.dispatch_bypass_filter(frame_system::RawOrigin::Root.into());
+			Self::deposit_event(Event::DispatchedAsRoot(
+				result.map(|_| ()).map_err(|e: DispatchErrorWithPostInfo| e.error),
+			));
+
+			Ok(Pays::No.into())
+		}
+	}
+}
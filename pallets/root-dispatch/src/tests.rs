@@ -0,0 +1,49 @@
+use crate::mock::*;
+
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{traits::BadOrigin, DispatchError};
+
+fn set_storage_call(key: &[u8], value: &[u8]) -> Box<Call> {
+	Box::new(Call::System(frame_system::Call::set_storage {
+		items: vec![(key.to_vec(), value.to_vec())],
+	}))
+}
+
+#[test]
+fn should_dispatch_as_root() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(RootDispatch::dispatch_as_root(
+			Origin::signed(1),
+			set_storage_call(b"key", b"value")
+		));
+
+		assert_eq!(sp_io::storage::get(b"key"), Some(b"value".to_vec()));
+		System::assert_last_event(Event::RootDispatch(crate::Event::DispatchedAsRoot(Ok(()))));
+	});
+}
+
+#[test]
+fn should_report_failed_call() {
+	new_test_ext().execute_with(|| {
+		// `remark_with_event` needs a signed origin
+		let call = Box::new(Call::System(frame_system::Call::remark_with_event {
+			remark: b"hello".to_vec(),
+		}));
+		assert_ok!(RootDispatch::dispatch_as_root(Origin::signed(1), call));
+
+		System::assert_last_event(Event::RootDispatch(crate::Event::DispatchedAsRoot(Err(
+			DispatchError::BadOrigin,
+		))));
+	});
+}
+
+#[test]
+fn should_not_dispatch_as_root_from_other_origins() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			RootDispatch::dispatch_as_root(Origin::signed(2), set_storage_call(b"key", b"value")),
+			BadOrigin
+		);
+		assert_eq!(sp_io::storage::get(b"key"), None);
+	});
+}
@@ -0,0 +1,63 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2022 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Estimated weights for pallet_root_dispatch
+//!
+//! THESE ARE NOT BENCHMARK RESULTS. Every weight, and every read and write count, is a hand
+//! estimate. Replace this file with the output of the command below before relying on it.
+
+// Command to generate the weights:
+// target/release/node-template
+// benchmark
+// --pallet
+// pallet_root_dispatch
+// --extrinsic
+// *
+// --template=.maintain/frame-weight-template.hbs
+// --execution=wasm
+// --wasm-execution=compiled
+// --output
+// pallets/root-dispatch/src/weights.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_root_dispatch.
+pub trait WeightInfo {
+	fn dispatch_as_root() -> Weight;
+}
+
+/// Weights for pallet_root_dispatch using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// The weight of the dispatched call is added on top of this.
+	fn dispatch_as_root() -> Weight {
+		(9_000_000 as Weight)
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// The weight of the dispatched call is added on top of this.
+	fn dispatch_as_root() -> Weight {
+		(9_000_000 as Weight)
+	}
+}
@@ -0,0 +1,137 @@
+//! A `pallet_contracts` chain extension giving contracts access to `pallet_kitties`.
+//!
+//! The function id selects the function, and its input and output are SCALE encoded:
+//!
+//! | id | function    | input                           | output              |
+//! |----|-------------|---------------------------------|---------------------|
+//! | 1  | `owns`      | `(AccountId, KittyIndex)`       | `bool`              |
+//! | 2  | `dna`       | `(AccountId, KittyIndex)`       | `Option<[u8; 16]>`  |
+//! | 3  | `price`     | `KittyIndex`                    | `Option<Balance>`   |
+//! | 4  | `transfer`  | `(AccountId, KittyIndex)`       | none                |
+//! | 5  | `set_price` | `(KittyIndex, Option<Balance>)` | none                |
+//! | 6  | `owner_of`  | `KittyIndex`                    | `Option<AccountId>` |
+//!
+//! `owner_of` looks the owner up in the `Owners` index of the pallet. `transfer` and `set_price`
+//! act on kitties owned by the calling contract, with the same checks as the extrinsics. Their
+//! outcome is returned as a `KittiesStatus` code.
+
+use crate::{Balance, Kitties, Runtime};
+use codec::{Decode, Encode};
+use frame_support::{
+	dispatch::{DispatchError, DispatchResult},
+	log,
+	traits::Get,
+	weights::Weight,
+};
+use pallet_contracts::chain_extension::{
+	ChainExtension, Environment, Ext, InitState, RetVal, SysConfig, UncheckedFrom,
+};
+use pallet_kitties::WeightInfo;
+
+type AccountId = <Runtime as frame_system::Config>::AccountId;
+type KittyIndex = <Runtime as pallet_kitties::Config>::KittyIndex;
+
+/// The outcome of `transfer` and `set_price`, returned to the contract as status code.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, sp_runtime::RuntimeDebug)]
+#[repr(u32)]
+pub enum KittiesStatus {
+	Success = 0,
+	/// The contract does not own the kitty.
+	NotOwner = 1,
+	/// Trading is paused by `Kitties::set_paused`.
+	TradingPaused = 2,
+	/// The call failed for another reason.
+	Failed = 3,
+}
+
+impl From<DispatchError> for KittiesStatus {
+	fn from(error: DispatchError) -> Self {
+		// `transfer` fails with `InvalidKittyId` and `set_price` with `NotOwner`
+		if error == pallet_kitties::Error::<Runtime>::InvalidKittyId.into() ||
+			error == pallet_kitties::Error::<Runtime>::NotOwner.into()
+		{
+			KittiesStatus::NotOwner
+		} else if error == pallet_kitties::Error::<Runtime>::TradingPaused.into() {
+			KittiesStatus::TradingPaused
+		} else {
+			KittiesStatus::Failed
+		}
+	}
+}
+
+/// The chain extension of this runtime, see the module docs.
+pub struct KittiesExtension;
+
+impl ChainExtension<Runtime> for KittiesExtension {
+	fn call<E: Ext>(func_id: u32, env: Environment<E, InitState>) -> Result<RetVal, DispatchError>
+	where
+		E: Ext<T = Runtime>,
+		<E::T as SysConfig>::AccountId: UncheckedFrom<<E::T as SysConfig>::Hash> + AsRef<[u8]>,
+	{
+		let mut env = env.buf_in_buf_out();
+		let read: Weight = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+
+		match func_id {
+			1 => {
+				let (owner, kitty_id): (AccountId, KittyIndex) = env.read_as()?;
+				env.charge_weight(read)?;
+
+				let owns = pallet_kitties::Kitties::<Runtime>::contains_key(&owner, kitty_id);
+				env.write(&owns.encode(), false, None)?;
+			},
+			2 => {
+				let (owner, kitty_id): (AccountId, KittyIndex) = env.read_as()?;
+				env.charge_weight(read)?;
+
+				let dna = Kitties::kitties(&owner, kitty_id).map(|kitty| kitty.dna());
+				env.write(&dna.encode(), false, None)?;
+			},
+			3 => {
+				let kitty_id: KittyIndex = env.read_as()?;
+				env.charge_weight(read)?;
+
+				let price: Option<Balance> = pallet_kitties::KittyPrices::<Runtime>::get(kitty_id);
+				env.write(&price.encode(), false, None)?;
+			},
+			4 => {
+				let (to, kitty_id): (AccountId, KittyIndex) = env.read_as()?;
+				env.charge_weight(<Runtime as pallet_kitties::Config>::WeightInfo::transfer())?;
+
+				let origin = frame_system::RawOrigin::Signed(env.ext().address().clone());
+				let result = Kitties::transfer(origin.into(), to, kitty_id);
+
+				return Ok(RetVal::Converging(status_of(result) as u32))
+			},
+			5 => {
+				let (kitty_id, new_price): (KittyIndex, Option<Balance>) = env.read_as()?;
+				env.charge_weight(<Runtime as pallet_kitties::Config>::WeightInfo::set_price())?;
+
+				let origin = frame_system::RawOrigin::Signed(env.ext().address().clone());
+				let result = Kitties::set_price(origin.into(), kitty_id, new_price);
+
+				return Ok(RetVal::Converging(status_of(result) as u32))
+			},
+			6 => {
+				let kitty_id: KittyIndex = env.read_as()?;
+				env.charge_weight(read)?;
+
+				let owner: Option<AccountId> = Kitties::owner_of(kitty_id);
+				env.write(&owner.encode(), false, None)?;
+			},
+			_ => {
+				log::error!(target: "runtime::contracts", "unknown kitties function id {}", func_id);
+				return Err(DispatchError::Other("unknown kitties function id"))
+			},
+		}
+
+		Ok(RetVal::Converging(KittiesStatus::Success as u32))
+	}
+
+	fn enabled() -> bool {
+		true
+	}
+}
+
+fn status_of(result: DispatchResult) -> KittiesStatus {
+	result.map_or_else(KittiesStatus::from, |()| KittiesStatus::Success)
+}
@@ -6,17 +6,25 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use codec::{Decode, Encode, MaxEncodedLen};
+use fp_rpc::TransactionStatus;
+use frame_system::EnsureRoot;
+use pallet_ethereum::{Call::transact, Transaction as EthereumTransaction};
+use pallet_evm::{Account as EVMAccount, FeeCalculator, Runner};
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{crypto::KeyTypeId, OpaqueMetadata, H160, H256, U256};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	traits::{
+		AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto, Dispatchable, IdentifyAccount,
+		NumberFor, OpaqueKeys, PostDispatchInfoOf, Verify,
+	},
+	transaction_validity::{TransactionSource, TransactionValidity, TransactionValidityError},
+	ApplyExtrinsicResult, MultiSignature, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -26,20 +34,27 @@ use sp_version::RuntimeVersion;
 // A few exports that help ease life for downstream crates.
 pub use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{ConstU128, ConstU32, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo},
+	traits::{
+		ConstU128, ConstU32, ConstU8, Contains, EnsureOneOf, EqualPrivilegeOnly, InstanceFilter,
+		KeyOwnerProofSystem, Randomness, StorageInfo,
+	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
+use pallet_session::historical as pallet_session_historical;
 pub use pallet_timestamp::Call as TimestampCall;
 use pallet_transaction_payment::CurrencyAdapter;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
+pub mod chain_extension;
+pub mod precompiles;
+pub mod validators;
 mod weights;
 
 /// Import the template pallet.
@@ -100,10 +115,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 110,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 4,
 	state_version: 1,
 };
 
@@ -143,11 +158,22 @@ parameter_types! {
 	pub const SS58Prefix: u8 = 42;
 }
 
+/// Rejects `pallet_kitties` calls that are paused by `Kitties::set_paused`.
+pub struct BaseFilter;
+impl Contains<Call> for BaseFilter {
+	fn contains(call: &Call) -> bool {
+		match call {
+			Call::Kitties(call) => !Kitties::is_call_paused(call),
+			_ => true,
+		}
+	}
+}
+
 // Configure FRAME pallets to include in runtime.
 
 impl frame_system::Config for Runtime {
 	/// The basic call filter to use in dispatchable.
-	type BaseCallFilter = frame_support::traits::Everything;
+	type BaseCallFilter = BaseFilter;
 	/// Block & extrinsics weights: base values and limits.
 	type BlockWeights = BlockWeights;
 	/// The maximum length of a block (in bytes).
@@ -201,7 +227,7 @@ impl pallet_randomness_collective_flip::Config for Runtime {}
 
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
-	type DisabledValidators = ();
+	type DisabledValidators = Session;
 	type MaxAuthorities = ConstU32<32>;
 }
 
@@ -209,7 +235,7 @@ impl pallet_grandpa::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -219,12 +245,55 @@ impl pallet_grandpa::Config for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation = pallet_grandpa::EquivocationHandler<
+		Self::KeyOwnerIdentification,
+		Offences,
+		ReportLongevity,
+	>;
 
 	type WeightInfo = ();
 	type MaxAuthorities = ConstU32<32>;
 }
 
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = HOURS;
+	pub const SessionOffset: BlockNumber = 0;
+	/// How long an equivocation report stays valid in the transaction pool.
+	pub const ReportLongevity: u64 = SessionPeriod::get() as u64 * 6;
+}
+
+impl pallet_session::Config for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type SessionManager =
+		pallet_session::historical::NoteHistoricalRoot<Self, validators::ValidatorManager>;
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type WeightInfo = ();
+}
+
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = ();
+	type FullIdentificationOf = validators::ValidatorIdentification;
+}
+
+impl pallet_offences::Config for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	type OnOffenceHandler = validators::DisableOffenders;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = Call;
+}
+
 parameter_types! {
 	pub const MinimumPeriod: u64 = SLOT_DURATION / 2;
 }
@@ -268,19 +337,343 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = 3 * DAYS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+type CouncilCollective = pallet_collective::Instance1;
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Root, or more than half of the council.
+pub type EnsureRootOrHalfCouncil = EnsureOneOf<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>,
+>;
+
+impl pallet_membership::Config<pallet_membership::Instance1> for Runtime {
+	type Event = Event;
+	type AddOrigin = EnsureRootOrHalfCouncil;
+	type RemoveOrigin = EnsureRootOrHalfCouncil;
+	type SwapOrigin = EnsureRootOrHalfCouncil;
+	type ResetOrigin = EnsureRootOrHalfCouncil;
+	type PrimeOrigin = EnsureRootOrHalfCouncil;
+	type MembershipInitialized = Council;
+	type MembershipChanged = Council;
+	type MaxMembers = CouncilMaxMembers;
+	type WeightInfo = pallet_membership::weights::SubstrateWeight<Runtime>;
+}
+
+/// Lets a council majority make runtime upgrades and other root calls.
+impl pallet_root_dispatch::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type RootOrigin = EnsureRootOrHalfCouncil;
+	type WeightInfo = pallet_root_dispatch::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	// Scheduled calls get most of the block, like in the Substrate node.
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = EnsureRootOrHalfCouncil;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = EqualPrivilegeOnly;
+	type PreimageProvider = ();
+	type NoPreimagePostponement = ();
+}
+
+parameter_types! {
+	// One storage item plus 8 bytes, and 33 bytes per proxy or 66 bytes per announcement.
+	pub const ProxyDepositBase: Balance = 1_080_000_000;
+	pub const ProxyDepositFactor: Balance = 330_000_000;
+	pub const AnnouncementDepositBase: Balance = 1_080_000_000;
+	pub const AnnouncementDepositFactor: Balance = 660_000_000;
+}
+
+/// The calls a proxy may make on behalf of its account.
+#[derive(
+	Copy,
+	Clone,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// Any `pallet_kitties` call.
+	Kitties,
+	/// Only `pallet_kitties::set_price` and `pallet_kitties::buy`, for trading bots.
+	KittiesTrading,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			ProxyType::Kitties => matches!(c, Call::Kitties(..)),
+			ProxyType::KittiesTrading => matches!(
+				c,
+				Call::Kitties(pallet_kitties::Call::set_price { .. }) |
+					Call::Kitties(pallet_kitties::Call::buy { .. })
+			),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			(_, ProxyType::Any) => false,
+			(ProxyType::Kitties, ProxyType::KittiesTrading) => true,
+			_ => false,
+		}
+	}
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = ConstU32<32>;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = ConstU32<32>;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
 ///// Configure the pallet-template in pallets/template.
 // impl pallet_template::Config for Runtime {
 // 	type Event = Event;
 // }
 
+parameter_types! {
+	pub const CommitDeposit: Balance = 1_000_000_000;
+	pub const ActionDeposit: Balance = 1_000_000_000;
+	// `RandomnessCollectiveFlip` only reports randomness as known since 81 blocks ago.
+	pub const RevealDelay: BlockNumber = 100;
+	pub const RevealWindow: BlockNumber = DAYS;
+	pub const HatchDelay: BlockNumber = 5 * MINUTES;
+	pub const MintBasePrice: Balance = 1_000_000_000_000;
+	pub const MintPriceIncrement: Balance = 1_000_000_000;
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+}
+
 impl pallet_kitties::Config for Runtime {
 	type Currency = Balances;
 	type Event = Event;
 	type KittyIndex = u32;
 	type Randomness = RandomnessCollectiveFlip;
+	type ForceOrigin = EnsureRootOrHalfCouncil;
+	type PauseOrigin = EnsureRootOrHalfCouncil;
+	type CommitDeposit = CommitDeposit;
+	type RevealDelay = RevealDelay;
+	type RevealWindow = RevealWindow;
+	type HatchDelay = HatchDelay;
+	type MaxHatchesPerBlock = ConstU32<32>;
+	type MintBasePrice = MintBasePrice;
+	type MintPriceIncrement = MintPriceIncrement;
+	type MaxGen0Supply = ConstU32<10_000>;
+	type PalletId = KittiesPalletId;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type MaxSponsoredPerBlock = ConstU32<8>;
+	type MaxSponsoredPerAccount = ConstU32<1>;
+	type ScheduledCall = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	type ActionDeposit = ActionDeposit;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = KittiesBenchmarkHelper;
 	type WeightInfo = weights::pallet_kitties::WeightInfo<Runtime>;
 }
 
+parameter_types! {
+	pub const GovernanceVotingPeriod: BlockNumber = 7 * DAYS;
+	pub const GovernanceEnactmentDelay: BlockNumber = DAYS;
+	pub const GovernanceProposalDeposit: Balance = 100_000_000_000_000;
+	pub const GovernanceMaxSaleFee: Permill = Permill::from_percent(20);
+	pub const GovernanceMaxBreedingCooldown: BlockNumber = 7 * DAYS;
+}
+
+impl pallet_kitties_governance::Config for Runtime {
+	type Event = Event;
+	type VotingPeriod = GovernanceVotingPeriod;
+	type EnactmentDelay = GovernanceEnactmentDelay;
+	type ProposalDeposit = GovernanceProposalDeposit;
+	type Quorum = ConstU32<10>;
+	type MaxSaleFee = GovernanceMaxSaleFee;
+	type MaxBreedingCooldown = GovernanceMaxBreedingCooldown;
+	type MaxActiveProposals = ConstU32<16>;
+	type MaxVoteKitties = ConstU32<64>;
+	type VoteWeight = pallet_kitties_governance::OnePerKitty;
+	type WeightInfo = pallet_kitties_governance::weights::SubstrateWeight<Runtime>;
+}
+
+/// Signs sell orders with a fresh sr25519 key for the `pallet_kitties` benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct KittiesBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_kitties::BenchmarkHelper<sp_runtime::MultiSigner, Signature>
+	for KittiesBenchmarkHelper
+{
+	fn create_public() -> sp_runtime::MultiSigner {
+		sp_io::crypto::sr25519_generate(KeyTypeId(*b"kitt"), None).into()
+	}
+
+	fn sign(public: &sp_runtime::MultiSigner, message: &[u8]) -> Signature {
+		match public {
+			sp_runtime::MultiSigner::Sr25519(public) =>
+				sp_io::crypto::sr25519_sign(KeyTypeId(*b"kitt"), public, message)
+					.expect("the key was generated by `create_public`; qed")
+					.into(),
+			_ => unreachable!("`create_public` only generates sr25519 keys; qed"),
+		}
+	}
+}
+
+parameter_types! {
+	pub const DepositPerItem: Balance = 1_000_000_000;
+	pub const DepositPerByte: Balance = 10_000_000;
+	// Lazy deletion of contracts gets a tenth of the block.
+	pub DeletionWeightLimit: Weight = Perbill::from_percent(10) * BlockWeights::get().max_block;
+	pub Schedule: pallet_contracts::Schedule<Runtime> = Default::default();
+}
+
+impl pallet_contracts::Config for Runtime {
+	type Time = Timestamp;
+	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type Event = Event;
+	type Call = Call;
+	/// Contracts reach the runtime through `KittiesExtension` only.
+	type CallFilter = frame_support::traits::Nothing;
+	type DepositPerItem = DepositPerItem;
+	type DepositPerByte = DepositPerByte;
+	type CallStack = [pallet_contracts::Frame<Self>; 31];
+	type WeightPrice = pallet_transaction_payment::Pallet<Self>;
+	type WeightInfo = pallet_contracts::weights::SubstrateWeight<Self>;
+	type ChainExtension = chain_extension::KittiesExtension;
+	type DeletionQueueDepth = ConstU32<128>;
+	type DeletionWeightLimit = DeletionWeightLimit;
+	type Schedule = Schedule;
+	type AddressGenerator = pallet_contracts::DefaultAddressGenerator;
+}
+
+parameter_types! {
+	pub const ChainId: u64 = 42;
+	pub BlockGasLimit: U256 = U256::from(
+		NORMAL_DISPATCH_RATIO * BlockWeights::get().max_block / precompiles::WEIGHT_PER_GAS
+	);
+	pub PrecompilesValue: precompiles::KittiesPrecompiles<Runtime> =
+		precompiles::KittiesPrecompiles::new();
+}
+
+/// The accounts an EVM address cannot be claimed away from: those holding kitties, or whose
+/// nonce shows they have sent transactions, EVM transactions included.
+pub struct AccountInUse;
+
+impl Contains<AccountId> for AccountInUse {
+	fn contains(account: &AccountId) -> bool {
+		Kitties::kitty_count(account) > 0 || System::account_nonce(account) > 0
+	}
+}
+
+impl pallet_evm_accounts::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type DefaultAccount = pallet_evm_accounts::PaddedAccount;
+	type DefaultAddress = pallet_evm_accounts::PaddedAccount;
+	type InUse = AccountInUse;
+	#[cfg(feature = "runtime-benchmarks")]
+	type BenchmarkHelper = EvmAccountsBenchmarkHelper;
+	type WeightInfo = pallet_evm_accounts::weights::SubstrateWeight<Runtime>;
+}
+
+/// Signs address claims with a fresh ecdsa key for the `pallet_evm_accounts` benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct EvmAccountsBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl pallet_evm_accounts::BenchmarkHelper for EvmAccountsBenchmarkHelper {
+	fn sign(hash: &[u8; 32]) -> [u8; 65] {
+		let public = sp_io::crypto::ecdsa_generate(KeyTypeId(*b"evma"), None);
+
+		sp_io::crypto::ecdsa_sign_prehashed(KeyTypeId(*b"evma"), &public, hash)
+			.expect("the key was just generated; qed")
+			.0
+	}
+}
+
+/// Prices gas at the fee of its weight, so EVM calls cost what Substrate calls of the same
+/// weight do.
+pub struct FixedGasPrice;
+
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> U256 {
+		precompiles::WEIGHT_PER_GAS.into()
+	}
+}
+
+impl pallet_evm::Config for Runtime {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = precompiles::FixedGasWeightMapping;
+	type BlockHashMapping = pallet_evm::SubstrateBlockHashMapping<Self>;
+	type CallOrigin = precompiles::EnsureAddressMapped;
+	type WithdrawOrigin = precompiles::EnsureAddressMapped;
+	type AddressMapping = precompiles::KittiesAddressMapping;
+	type Currency = Balances;
+	type Event = Event;
+	type Runner = pallet_evm::runner::stack::Runner<Self>;
+	type PrecompilesType = precompiles::KittiesPrecompiles<Runtime>;
+	type PrecompilesValue = PrecompilesValue;
+	type ChainId = ChainId;
+	type BlockGasLimit = BlockGasLimit;
+	type OnChargeTransaction = pallet_evm::EVMCurrencyAdapter<Balances, ()>;
+	type FindAuthor = ();
+}
+
+impl pallet_ethereum::Config for Runtime {
+	type Event = Event;
+	type StateRoot = pallet_ethereum::IntermediateStateRoot;
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub enum Runtime where
@@ -299,6 +692,19 @@ construct_runtime!(
 		// Include the custom logic from the pallet-template in the runtime.
 		// TemplateModule: pallet_template,
 		Kitties: pallet_kitties,
+		Contracts: pallet_contracts,
+		EVM: pallet_evm,
+		Council: pallet_collective::<Instance1>,
+		CouncilMembership: pallet_membership::<Instance1>,
+		RootDispatch: pallet_root_dispatch,
+		KittiesGovernance: pallet_kitties_governance,
+		Scheduler: pallet_scheduler,
+		Proxy: pallet_proxy,
+		Session: pallet_session,
+		Historical: pallet_session_historical::{Pallet},
+		Offences: pallet_offences,
+		EvmAccounts: pallet_evm_accounts,
+		Ethereum: pallet_ethereum::{Pallet, Call, Storage, Event, Config, Origin},
 	}
 );
 
@@ -317,10 +723,18 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_kitties::ChargeSponsoredTransactionPayment<Runtime>,
+	pallet_kitties::CheckCallFilter<Runtime>,
+	pallet_kitties::CheckKittyCall<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
-pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+///
+/// Besides signed and unsigned extrinsics, it holds the Ethereum transactions of
+/// `pallet_ethereum`, which carry their own signature and skip `SignedExtra`.
+pub type UncheckedExtrinsic =
+	fp_self_contained::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
+/// Extrinsic type that has already been checked.
+pub type CheckedExtrinsic = fp_self_contained::CheckedExtrinsic<AccountId, Call, SignedExtra, H160>;
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -330,6 +744,75 @@ pub type Executive = frame_executive::Executive<
 	AllPalletsWithSystem,
 >;
 
+impl fp_self_contained::SelfContainedCall for Call {
+	type SignedInfo = H160;
+
+	fn is_self_contained(&self) -> bool {
+		match self {
+			Call::Ethereum(call) => call.is_self_contained(),
+			_ => false,
+		}
+	}
+
+	fn check_self_contained(&self) -> Option<Result<Self::SignedInfo, TransactionValidityError>> {
+		match self {
+			Call::Ethereum(call) => call.check_self_contained(),
+			_ => None,
+		}
+	}
+
+	fn validate_self_contained(&self, info: &Self::SignedInfo) -> Option<TransactionValidity> {
+		match self {
+			Call::Ethereum(call) => call.validate_self_contained(info),
+			_ => None,
+		}
+	}
+
+	fn pre_dispatch_self_contained(
+		&self,
+		info: &Self::SignedInfo,
+	) -> Option<Result<(), TransactionValidityError>> {
+		match self {
+			Call::Ethereum(call) => call.pre_dispatch_self_contained(info),
+			_ => None,
+		}
+	}
+
+	fn apply_self_contained(
+		self,
+		info: Self::SignedInfo,
+	) -> Option<sp_runtime::DispatchResultWithInfo<PostDispatchInfoOf<Self>>> {
+		match self {
+			call @ Call::Ethereum(pallet_ethereum::Call::transact { .. }) => Some(
+				call.dispatch(Origin::from(pallet_ethereum::RawOrigin::EthereumTransaction(info))),
+			),
+			_ => None,
+		}
+	}
+}
+
+/// Wraps the Ethereum transactions submitted through the eth RPC into extrinsics.
+pub struct TransactionConverter;
+
+impl fp_rpc::ConvertTransaction<UncheckedExtrinsic> for TransactionConverter {
+	fn convert_transaction(&self, transaction: EthereumTransaction) -> UncheckedExtrinsic {
+		UncheckedExtrinsic::new_unsigned(
+			pallet_ethereum::Call::<Runtime>::transact { transaction }.into(),
+		)
+	}
+}
+
+impl fp_rpc::ConvertTransaction<opaque::UncheckedExtrinsic> for TransactionConverter {
+	fn convert_transaction(&self, transaction: EthereumTransaction) -> opaque::UncheckedExtrinsic {
+		let extrinsic = UncheckedExtrinsic::new_unsigned(
+			pallet_ethereum::Call::<Runtime>::transact { transaction }.into(),
+		);
+		let encoded = extrinsic.encode();
+		opaque::UncheckedExtrinsic::decode(&mut &encoded[..])
+			.expect("Encoded extrinsic is always valid")
+	}
+}
+
 #[cfg(feature = "runtime-benchmarks")]
 #[macro_use]
 extern crate frame_benchmarking;
@@ -340,9 +823,17 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_collective, Council]
+		[pallet_membership, CouncilMembership]
+		[pallet_proxy, Proxy]
+		[pallet_root_dispatch, RootDispatch]
+		[pallet_scheduler, Scheduler]
 		[pallet_timestamp, Timestamp]
 		// [pallet_template, TemplateModule]
 		[pallet_kitties, Kitties]
+		[pallet_kitties_governance, KittiesGovernance]
+		[pallet_contracts, Contracts]
+		[pallet_evm_accounts, EvmAccounts]
 	);
 }
 
@@ -436,23 +927,27 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+				.map(|proof| proof.encode())
+				.map(fg_primitives::OpaqueKeyOwnershipProof::new)
 		}
 	}
 
@@ -462,6 +957,201 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_runtime_api::KittiesApi<Block, Balance, u32> for Runtime {
+		fn mint_price() -> Balance {
+			Kitties::mint_price()
+		}
+
+		fn preview_breeding(
+			kitty1: pallet_kitties::KittyOrDna<u32>,
+			kitty2: pallet_kitties::KittyOrDna<u32>,
+		) -> Option<pallet_kitties::BreedingPreview> {
+			Kitties::preview_breeding(kitty1, kitty2)
+		}
+	}
+
+	impl fp_rpc::EthereumRuntimeRPCApi<Block> for Runtime {
+		fn chain_id() -> u64 {
+			<Runtime as pallet_evm::Config>::ChainId::get()
+		}
+
+		fn account_basic(address: H160) -> EVMAccount {
+			EVM::account_basic(&address)
+		}
+
+		fn gas_price() -> U256 {
+			<Runtime as pallet_evm::Config>::FeeCalculator::min_gas_price()
+		}
+
+		fn account_code_at(address: H160) -> Vec<u8> {
+			EVM::account_codes(address)
+		}
+
+		fn author() -> H160 {
+			<pallet_evm::Pallet<Runtime>>::find_author()
+		}
+
+		fn storage_at(address: H160, index: U256) -> H256 {
+			let mut tmp = [0u8; 32];
+			index.to_big_endian(&mut tmp);
+			EVM::account_storages(address, H256::from_slice(&tmp[..]))
+		}
+
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<pallet_evm::CallInfo, sp_runtime::DispatchError> {
+			let config = if estimate {
+				let mut config = <Runtime as pallet_evm::Config>::config().clone();
+				config.estimate = true;
+				Some(config)
+			} else {
+				None
+			};
+
+			<Runtime as pallet_evm::Config>::Runner::call(
+				from,
+				to,
+				data,
+				value,
+				gas_limit.low_u64(),
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.unwrap_or_default(),
+				config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
+			).map_err(|err| err.into())
+		}
+
+		fn create(
+			from: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			max_fee_per_gas: Option<U256>,
+			max_priority_fee_per_gas: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			access_list: Option<Vec<(H160, Vec<H256>)>>,
+		) -> Result<pallet_evm::CreateInfo, sp_runtime::DispatchError> {
+			let config = if estimate {
+				let mut config = <Runtime as pallet_evm::Config>::config().clone();
+				config.estimate = true;
+				Some(config)
+			} else {
+				None
+			};
+
+			<Runtime as pallet_evm::Config>::Runner::create(
+				from,
+				data,
+				value,
+				gas_limit.low_u64(),
+				max_fee_per_gas,
+				max_priority_fee_per_gas,
+				nonce,
+				access_list.unwrap_or_default(),
+				config.as_ref().unwrap_or(<Runtime as pallet_evm::Config>::config()),
+			).map_err(|err| err.into())
+		}
+
+		fn current_transaction_statuses() -> Option<Vec<TransactionStatus>> {
+			Ethereum::current_transaction_statuses()
+		}
+
+		fn current_block() -> Option<pallet_ethereum::Block> {
+			Ethereum::current_block()
+		}
+
+		fn current_receipts() -> Option<Vec<pallet_ethereum::Receipt>> {
+			Ethereum::current_receipts()
+		}
+
+		fn current_all() -> (
+			Option<pallet_ethereum::Block>,
+			Option<Vec<pallet_ethereum::Receipt>>,
+			Option<Vec<TransactionStatus>>,
+		) {
+			(
+				Ethereum::current_block(),
+				Ethereum::current_receipts(),
+				Ethereum::current_transaction_statuses(),
+			)
+		}
+
+		fn extrinsic_filter(
+			xts: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Vec<EthereumTransaction> {
+			xts.into_iter().filter_map(|xt| match xt.0.function {
+				Call::Ethereum(transact { transaction }) => Some(transaction),
+				_ => None,
+			}).collect()
+		}
+
+		fn elasticity() -> Option<Permill> {
+			// there is no base fee pallet, gas has the fixed price of `FixedGasPrice`
+			None
+		}
+	}
+
+	impl fp_rpc::ConvertTransactionRuntimeApi<Block> for Runtime {
+		fn convert_transaction(transaction: EthereumTransaction) -> <Block as BlockT>::Extrinsic {
+			UncheckedExtrinsic::new_unsigned(
+				pallet_ethereum::Call::<Runtime>::transact { transaction }.into(),
+			)
+		}
+	}
+
+	impl pallet_contracts_rpc_runtime_api::ContractsApi<Block, AccountId, Balance, BlockNumber, Hash>
+		for Runtime
+	{
+		fn call(
+			origin: AccountId,
+			dest: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			input_data: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractExecResult<Balance> {
+			Contracts::bare_call(origin, dest, value, gas_limit, storage_deposit_limit, input_data, true)
+		}
+
+		fn instantiate(
+			origin: AccountId,
+			value: Balance,
+			gas_limit: u64,
+			storage_deposit_limit: Option<Balance>,
+			code: pallet_contracts_primitives::Code<Hash>,
+			data: Vec<u8>,
+			salt: Vec<u8>,
+		) -> pallet_contracts_primitives::ContractInstantiateResult<AccountId, Balance> {
+			Contracts::bare_instantiate(origin, value, gas_limit, storage_deposit_limit, code, data, salt, true)
+		}
+
+		fn upload_code(
+			origin: AccountId,
+			code: Vec<u8>,
+			storage_deposit_limit: Option<Balance>,
+		) -> pallet_contracts_primitives::CodeUploadResult<Hash, Balance> {
+			Contracts::bare_upload_code(origin, code, storage_deposit_limit)
+		}
+
+		fn get_storage(
+			address: AccountId,
+			key: [u8; 32],
+		) -> pallet_contracts_primitives::GetStorageResult {
+			Contracts::get_storage(address, key)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -477,6 +1167,21 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> (Weight, Weight) {
+			// NOTE: intentional unwrap: we don't want to propagate the error backwards, and want to
+			// have a backtrace here. If any of the pre/post migration checks fail, we shall stop
+			// right here and right now.
+			let weight = Executive::try_runtime_upgrade().unwrap();
+			(weight, BlockWeights::get().max_block)
+		}
+
+		fn execute_block_no_check(block: Block) -> Weight {
+			Executive::execute_block_no_check(block)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -0,0 +1,383 @@
+//! The EVM precompiles of this runtime: the standard Ethereum ones, and `pallet_kitties` as an
+//! ERC-721 token at `KITTIES_ADDRESS`.
+//!
+//! The token reads and writes the pallet's storage, so ownership is the same whether it is read
+//! through the EVM or through Substrate. Addresses and accounts are mapped both ways by
+//! `pallet_evm_accounts`. A Substrate account that has not claimed an address has none, so
+//! `ownerOf` and `getApproved` revert for its kitties, which it moves with the Substrate calls.
+//!
+//! Only the transfers and approvals made through the token emit EVM logs. Those made with the
+//! pallet's own calls, by Substrate accounts or by contracts, deposit the pallet's events only,
+//! so an EVM indexer following the logs misses them.
+
+use crate::{AccountId, EvmAccounts, Kitties, Runtime, Weight};
+use fp_evm::{
+	Context, ExitError, ExitRevert, ExitSucceed, Log, Precompile, PrecompileFailure,
+	PrecompileOutput, PrecompileResult, PrecompileSet,
+};
+use frame_support::{dispatch::DispatchResult, traits::Get};
+use frame_system::RawOrigin;
+use pallet_evm::{AddressMapping, EnsureAddressOrigin, GasWeightMapping};
+use pallet_evm_precompile_simple::{ECRecover, Identity, Ripemd160, Sha256};
+use pallet_kitties::WeightInfo;
+use sp_core::{H160, H256, U256};
+use sp_io::hashing::keccak_256;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// The address of the kitties ERC-721 precompile.
+pub const KITTIES_ADDRESS: u64 = 2048;
+
+/// The weight of one gas unit.
+pub const WEIGHT_PER_GAS: u64 = 20_000;
+
+/// Converts between gas and weight at the fixed `WEIGHT_PER_GAS` rate.
+pub struct FixedGasWeightMapping;
+
+impl GasWeightMapping for FixedGasWeightMapping {
+	fn gas_to_weight(gas: u64) -> Weight {
+		gas.saturating_mul(WEIGHT_PER_GAS)
+	}
+
+	fn weight_to_gas(weight: Weight) -> u64 {
+		weight / WEIGHT_PER_GAS
+	}
+}
+
+/// Maps an EVM address to its account in `pallet_evm_accounts`.
+///
+/// Unlike hashing, this can be reversed by `address_of`, which `ownerOf` relies on.
+pub struct KittiesAddressMapping;
+
+impl AddressMapping<AccountId> for KittiesAddressMapping {
+	fn into_account_id(address: H160) -> AccountId {
+		EvmAccounts::account_of(address)
+	}
+}
+
+/// The EVM address of `account` in `pallet_evm_accounts`, if it has one.
+pub fn address_of(account: &AccountId) -> Option<H160> {
+	EvmAccounts::address_of(account)
+}
+
+/// Lets the signed origin of an account call and withdraw as the address mapped to it by
+/// `KittiesAddressMapping`.
+pub struct EnsureAddressMapped;
+
+impl<OuterOrigin> EnsureAddressOrigin<OuterOrigin> for EnsureAddressMapped
+where
+	OuterOrigin: Into<Result<RawOrigin<AccountId>, OuterOrigin>> + From<RawOrigin<AccountId>>,
+{
+	type Success = AccountId;
+
+	fn try_address_origin(address: &H160, origin: OuterOrigin) -> Result<AccountId, OuterOrigin> {
+		origin.into().and_then(|origin| match origin {
+			RawOrigin::Signed(who) if who == KittiesAddressMapping::into_account_id(*address) =>
+				Ok(who),
+			origin => Err(OuterOrigin::from(origin)),
+		})
+	}
+}
+
+fn hash(a: u64) -> H160 {
+	H160::from_low_u64_be(a)
+}
+
+/// The EVM accounts of the precompiles at genesis.
+///
+/// Solidity checks that the callee of a call has code, so each precompile gets a body that
+/// reverts when it runs. The precompile itself answers calls before that code is reached.
+#[cfg(feature = "std")]
+pub fn genesis_accounts() -> std::collections::BTreeMap<H160, pallet_evm::GenesisAccount> {
+	// PUSH1 0x00 PUSH1 0x00 REVERT
+	let revert = vec![0x60, 0x00, 0x60, 0x00, 0xfd];
+
+	KittiesPrecompiles::<Runtime>::used_addresses()
+		.into_iter()
+		.map(|address| {
+			let account = pallet_evm::GenesisAccount {
+				nonce: Default::default(),
+				balance: Default::default(),
+				storage: Default::default(),
+				code: revert.clone(),
+			};
+			(address, account)
+		})
+		.collect()
+}
+
+/// The precompiles at `used_addresses`.
+pub struct KittiesPrecompiles<R>(PhantomData<R>);
+
+impl<R> KittiesPrecompiles<R> {
+	pub fn new() -> Self {
+		Self(Default::default())
+	}
+
+	pub fn used_addresses() -> [H160; 5] {
+		[hash(1), hash(2), hash(3), hash(4), hash(KITTIES_ADDRESS)]
+	}
+}
+
+impl PrecompileSet for KittiesPrecompiles<Runtime> {
+	fn execute(
+		&self,
+		address: H160,
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+		is_static: bool,
+	) -> Option<PrecompileResult> {
+		match address {
+			// Ethereum precompiles
+			a if a == hash(1) => Some(ECRecover::execute(input, target_gas, context, is_static)),
+			a if a == hash(2) => Some(Sha256::execute(input, target_gas, context, is_static)),
+			a if a == hash(3) => Some(Ripemd160::execute(input, target_gas, context, is_static)),
+			a if a == hash(4) => Some(Identity::execute(input, target_gas, context, is_static)),
+			// Kitties
+			a if a == hash(KITTIES_ADDRESS) =>
+				Some(Erc721Kitties::execute(input, target_gas, context, is_static)),
+			_ => None,
+		}
+	}
+
+	fn is_precompile(&self, address: H160) -> bool {
+		Self::used_addresses().contains(&address)
+	}
+}
+
+/// `balanceOf(address)`
+const SELECTOR_BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+/// `ownerOf(uint256)`
+const SELECTOR_OWNER_OF: [u8; 4] = [0x63, 0x52, 0x21, 0x1e];
+/// `getApproved(uint256)`
+const SELECTOR_GET_APPROVED: [u8; 4] = [0x08, 0x18, 0x12, 0xfc];
+/// `transferFrom(address,address,uint256)`
+const SELECTOR_TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+/// `approve(address,uint256)`
+const SELECTOR_APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+
+const EVENT_TRANSFER: &[u8] = b"Transfer(address,address,uint256)";
+const EVENT_APPROVAL: &[u8] = b"Approval(address,address,uint256)";
+
+type KittyIndex = <Runtime as pallet_kitties::Config>::KittyIndex;
+
+/// `pallet_kitties` as an ERC-721 token.
+///
+/// Implements `balanceOf`, `ownerOf`, `getApproved`, `transferFrom` and `approve`, and emits the
+/// `Transfer` and `Approval` events for the transfers and approvals made through it. `balanceOf`
+/// reverts for the zero address, as ERC-721 requires.
+pub struct Erc721Kitties;
+
+impl Precompile for Erc721Kitties {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+		is_static: bool,
+	) -> PrecompileResult {
+		let mut input = AbiReader::new(input)?;
+
+		match input.selector() {
+			SELECTOR_BALANCE_OF => {
+				let cost = gas_cost(
+					<Runtime as frame_system::Config>::DbWeight::get().reads(2),
+					target_gas,
+				)?;
+				let owner = input.address()?;
+				if owner.is_zero() {
+					return Err(revert(cost, "zero address is not a valid owner"))
+				}
+				let owner = KittiesAddressMapping::into_account_id(owner);
+
+				Ok(succeed(cost, encode_uint(Kitties::kitty_count(&owner).into()), vec![]))
+			},
+			SELECTOR_OWNER_OF => {
+				let cost = gas_cost(
+					<Runtime as frame_system::Config>::DbWeight::get().reads(3),
+					target_gas,
+				)?;
+				let owner = Kitties::owner_of(input.kitty_id()?)
+					.ok_or_else(|| revert(cost, "kitty does not exist"))?;
+				let owner =
+					address_of(&owner).ok_or_else(|| revert(cost, "owner has no EVM address"))?;
+
+				Ok(succeed(cost, encode_address(owner), vec![]))
+			},
+			SELECTOR_GET_APPROVED => {
+				let cost = gas_cost(
+					<Runtime as frame_system::Config>::DbWeight::get().reads(4),
+					target_gas,
+				)?;
+				let kitty_id = input.kitty_id()?;
+				if Kitties::owner_of(kitty_id).is_none() {
+					return Err(revert(cost, "kitty does not exist"))
+				}
+
+				let approved = match Kitties::approvals(kitty_id) {
+					Some(account) => address_of(&account)
+						.ok_or_else(|| revert(cost, "approved account has no EVM address"))?,
+					None => H160::zero(),
+				};
+
+				Ok(succeed(cost, encode_address(approved), vec![]))
+			},
+			SELECTOR_TRANSFER_FROM => {
+				let cost = gas_cost(
+					<Runtime as pallet_kitties::Config>::WeightInfo::transfer_from(),
+					target_gas,
+				)?;
+				ensure_not_static(is_static, cost)?;
+
+				let (from, to, kitty_id) = (input.address()?, input.address()?, input.kitty_id()?);
+
+				dispatch_result(
+					cost,
+					Kitties::transfer_from(
+						caller_origin(context),
+						KittiesAddressMapping::into_account_id(from),
+						KittiesAddressMapping::into_account_id(to),
+						kitty_id,
+					),
+				)?;
+
+				// a transfer to the owner itself changes nothing, so there is nothing to log
+				let logs = if from == to {
+					vec![]
+				} else {
+					vec![log3(EVENT_TRANSFER, from, to, kitty_id)]
+				};
+				Ok(succeed(cost, vec![], logs))
+			},
+			SELECTOR_APPROVE => {
+				let cost = gas_cost(
+					<Runtime as pallet_kitties::Config>::WeightInfo::approve(),
+					target_gas,
+				)?;
+				ensure_not_static(is_static, cost)?;
+
+				let (approved, kitty_id) = (input.address()?, input.kitty_id()?);
+
+				// The zero address clears the approval.
+				let approved_account =
+					(!approved.is_zero()).then(|| KittiesAddressMapping::into_account_id(approved));
+
+				dispatch_result(
+					cost,
+					Kitties::approve(caller_origin(context), approved_account, kitty_id),
+				)?;
+
+				let log = log3(EVENT_APPROVAL, context.caller, approved, kitty_id);
+				Ok(succeed(cost, vec![], vec![log]))
+			},
+			_ => Err(revert(0, "unknown selector")),
+		}
+	}
+}
+
+fn caller_origin(context: &Context) -> crate::Origin {
+	RawOrigin::Signed(KittiesAddressMapping::into_account_id(context.caller)).into()
+}
+
+fn gas_cost(weight: Weight, target_gas: Option<u64>) -> Result<u64, PrecompileFailure> {
+	let cost = FixedGasWeightMapping::weight_to_gas(weight);
+
+	match target_gas {
+		Some(target_gas) if cost > target_gas =>
+			Err(PrecompileFailure::Error { exit_status: ExitError::OutOfGas }),
+		_ => Ok(cost),
+	}
+}
+
+fn ensure_not_static(is_static: bool, cost: u64) -> Result<(), PrecompileFailure> {
+	if is_static {
+		Err(revert(cost, "cannot modify state in a static call"))
+	} else {
+		Ok(())
+	}
+}
+
+fn dispatch_result(cost: u64, result: DispatchResult) -> Result<(), PrecompileFailure> {
+	result.map_err(|error| {
+		let message: &'static str = error.into();
+		revert(cost, message)
+	})
+}
+
+fn succeed(cost: u64, output: Vec<u8>, logs: Vec<Log>) -> PrecompileOutput {
+	PrecompileOutput { exit_status: ExitSucceed::Returned, cost, output, logs }
+}
+
+fn revert(cost: u64, message: &str) -> PrecompileFailure {
+	PrecompileFailure::Revert {
+		exit_status: ExitRevert::Reverted,
+		output: message.as_bytes().to_vec(),
+		cost,
+	}
+}
+
+fn log3(event: &[u8], from: H160, to: H160, kitty_id: KittyIndex) -> Log {
+	Log {
+		address: hash(KITTIES_ADDRESS),
+		topics: vec![
+			H256(keccak_256(event)),
+			H256::from(from),
+			H256::from(to),
+			H256::from_slice(&encode_uint(kitty_id.into())),
+		],
+		data: vec![],
+	}
+}
+
+fn encode_uint(value: U256) -> Vec<u8> {
+	let mut word = [0u8; 32];
+	value.to_big_endian(&mut word);
+	word.to_vec()
+}
+
+fn encode_address(address: H160) -> Vec<u8> {
+	H256::from(address).as_bytes().to_vec()
+}
+
+/// Reads the selector and the 32-byte arguments of an ABI encoded call.
+struct AbiReader<'a> {
+	input: &'a [u8],
+	cursor: usize,
+}
+
+impl<'a> AbiReader<'a> {
+	fn new(input: &'a [u8]) -> Result<Self, PrecompileFailure> {
+		if input.len() < 4 {
+			return Err(revert(0, "input too short"))
+		}
+
+		Ok(Self { input, cursor: 4 })
+	}
+
+	fn selector(&self) -> [u8; 4] {
+		let mut selector = [0u8; 4];
+		selector.copy_from_slice(&self.input[..4]);
+		selector
+	}
+
+	fn word(&mut self) -> Result<&'a [u8], PrecompileFailure> {
+		let word = self
+			.input
+			.get(self.cursor..self.cursor + 32)
+			.ok_or_else(|| revert(0, "input too short"))?;
+		self.cursor += 32;
+		Ok(word)
+	}
+
+	fn address(&mut self) -> Result<H160, PrecompileFailure> {
+		Ok(H160::from_slice(&self.word()?[12..]))
+	}
+
+	fn kitty_id(&mut self) -> Result<KittyIndex, PrecompileFailure> {
+		let value = U256::from_big_endian(self.word()?);
+		if value > U256::from(KittyIndex::max_value()) {
+			return Err(revert(0, "kitty id out of range"))
+		}
+
+		Ok(value.low_u32())
+	}
+}
@@ -0,0 +1,95 @@
+//! The validator set and the punishment of its offenders.
+//!
+//! There is no staking, so the validators are those of the genesis session keys, kept from session
+//! to session. `pallet_session::historical` keeps a root of the keys of every session, against
+//! which GRANDPA checks the key ownership proofs of the equivocation reports, and
+//! `pallet_offences` hands the offenders to [`DisableOffenders`].
+//!
+//! There are no funds at stake to slash, so an offender is disabled for the rest of the session
+//! instead: Aura rejects its blocks through `pallet_session`'s `DisabledValidators`. At most a
+//! third of the validators, but at least one, are disabled at once, and a lone validator never
+//! is, so that block production never stalls.
+
+use crate::{AccountId, Runtime, Session};
+use frame_support::{traits::Get, weights::Weight};
+use pallet_session::historical::IdentificationTuple;
+use sp_runtime::{
+	traits::{Convert, Zero},
+	Perbill,
+};
+use sp_staking::{
+	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
+	SessionIndex,
+};
+use sp_std::prelude::*;
+
+/// Identifies a validator by its account alone, as there is no exposure to identify.
+pub struct ValidatorIdentification;
+
+impl Convert<AccountId, Option<()>> for ValidatorIdentification {
+	fn convert(_: AccountId) -> Option<()> {
+		Some(())
+	}
+}
+
+/// Keeps the validators of the current session for the next one.
+///
+/// The new validator set is given explicitly, rather than left unchanged, so that the historical
+/// pallet stores a root of the keys of every session. The validators are not known yet when the
+/// genesis sessions are built, so the equivocations of the first two sessions can only be reported
+/// while their session lasts.
+pub struct ValidatorManager;
+
+impl pallet_session::historical::SessionManager<AccountId, ()> for ValidatorManager {
+	fn new_session(_: SessionIndex) -> Option<Vec<(AccountId, ())>> {
+		let validators = Session::validators();
+		// the genesis validators are those of the genesis session keys
+		if validators.is_empty() {
+			return None
+		}
+		Some(validators.into_iter().map(|validator| (validator, ())).collect())
+	}
+
+	fn end_session(_: SessionIndex) {}
+
+	fn start_session(_: SessionIndex) {}
+}
+
+/// Disables the reported offenders until the end of the session, up to [`max_disabled`] of the
+/// validators.
+pub struct DisableOffenders;
+
+impl OnOffenceHandler<AccountId, IdentificationTuple<Runtime>, Weight> for DisableOffenders {
+	fn on_offence(
+		offenders: &[OffenceDetails<AccountId, IdentificationTuple<Runtime>>],
+		slash_fraction: &[Perbill],
+		_session: SessionIndex,
+		disable_strategy: DisableStrategy,
+	) -> Weight {
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+		let max_disabled = max_disabled(Session::validators().len());
+		let mut weight = db_weight.reads(1);
+
+		for (details, fraction) in offenders.iter().zip(slash_fraction) {
+			let disable = match disable_strategy {
+				DisableStrategy::Never => false,
+				DisableStrategy::WhenSlashed => !fraction.is_zero(),
+				DisableStrategy::Always => true,
+			};
+			weight = weight.saturating_add(db_weight.reads(1));
+			if disable && Session::disabled_validators().len() < max_disabled {
+				Session::disable(&details.offender.0);
+				weight = weight.saturating_add(db_weight.reads_writes(1, 1));
+			}
+		}
+
+		weight
+	}
+}
+
+/// The number of validators out of `validators` that may be disabled at once: a third, rounded
+/// down, but at least one so that small sets can punish an offender, while always leaving one
+/// validator to author blocks.
+pub fn max_disabled(validators: usize) -> usize {
+	(validators / 3).max(1).min(validators.saturating_sub(1))
+}
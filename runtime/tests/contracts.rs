@@ -0,0 +1,140 @@
+//! Calls the kitties chain extension from a contract deployed on the runtime.
+
+use codec::{Decode, Encode};
+use frame_support::traits::GenesisBuild;
+use node_template_runtime::{
+	chain_extension::KittiesStatus, AccountId, Balance, Contracts, Kitties, Origin, Runtime, System,
+};
+use pallet_contracts_primitives::Code;
+use pallet_kitties::{Kitty, PauseFlags};
+use sp_runtime::AccountId32;
+
+const ALICE: AccountId = AccountId32::new([1; 32]);
+const BOB: AccountId = AccountId32::new([2; 32]);
+
+const GAS_LIMIT: u64 = 100_000_000_000;
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> { balances: vec![(ALICE, 1 << 60)] }
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+fn deploy() -> AccountId {
+	let wasm = wat::parse_file("tests/fixtures/kitties_extension.wat").unwrap();
+
+	Contracts::bare_instantiate(
+		ALICE,
+		1_000_000,
+		GAS_LIMIT,
+		None,
+		Code::Upload(wasm.into()),
+		vec![],
+		vec![],
+		false,
+	)
+	.result
+	.unwrap()
+	.account_id
+}
+
+/// Call `func_id` of the chain extension through `contract`, returning the status code and the
+/// function output.
+fn call_extension(contract: &AccountId, func_id: u32, input: impl Encode) -> (u32, Vec<u8>) {
+	let mut data = func_id.encode();
+	input.encode_to(&mut data);
+
+	let output = Contracts::bare_call(ALICE, contract.clone(), 0, GAS_LIMIT, None, data, false)
+		.result
+		.unwrap()
+		.data
+		.0;
+
+	(u32::decode(&mut &output[..4]).unwrap(), output[4..].to_vec())
+}
+
+fn query<T: Decode>(contract: &AccountId, func_id: u32, input: impl Encode) -> T {
+	let (status, output) = call_extension(contract, func_id, input);
+	assert_eq!(status, KittiesStatus::Success as u32);
+
+	T::decode(&mut &output[..]).unwrap()
+}
+
+#[test]
+fn contract_should_query_kitties() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+		pallet_kitties::Kitties::<Runtime>::insert(&contract, 0, Kitty([1; 16]));
+		pallet_kitties::KittyPrices::<Runtime>::insert(0, 100);
+
+		assert!(query::<bool>(&contract, 1, (&contract, 0u32)));
+		assert!(!query::<bool>(&contract, 1, (&BOB, 0u32)));
+
+		assert_eq!(query::<Option<[u8; 16]>>(&contract, 2, (&contract, 0u32)), Some([1; 16]));
+		assert_eq!(query::<Option<[u8; 16]>>(&contract, 2, (&contract, 1u32)), None);
+
+		assert_eq!(query::<Option<Balance>>(&contract, 3, 0u32), Some(100));
+		assert_eq!(query::<Option<Balance>>(&contract, 3, 1u32), None);
+	});
+}
+
+#[test]
+fn contract_should_look_up_kitty_owners() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+		let kitty_id = Kitties::next_kitty_id();
+		assert_eq!(query::<Option<AccountId>>(&contract, 6, kitty_id), None);
+
+		assert!(Kitties::create(Origin::signed(ALICE)).is_ok());
+		assert_eq!(query::<Option<AccountId>>(&contract, 6, kitty_id), Some(ALICE));
+
+		assert!(Kitties::transfer(Origin::signed(ALICE), BOB, kitty_id).is_ok());
+		assert_eq!(query::<Option<AccountId>>(&contract, 6, kitty_id), Some(BOB));
+	});
+}
+
+#[test]
+fn contract_should_list_and_transfer_own_kitty() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+		pallet_kitties::Kitties::<Runtime>::insert(&contract, 0, Kitty([1; 16]));
+
+		let (status, _) = call_extension(&contract, 5, (0u32, Some::<Balance>(100)));
+		assert_eq!(status, KittiesStatus::Success as u32);
+		assert_eq!(Kitties::kitty_prices(0), Some(100));
+
+		let (status, _) = call_extension(&contract, 4, (&BOB, 0u32));
+		assert_eq!(status, KittiesStatus::Success as u32);
+		assert!(pallet_kitties::Kitties::<Runtime>::contains_key(&BOB, 0));
+
+		// the kitty now belongs to Bob
+		let (status, _) = call_extension(&contract, 4, (&ALICE, 0u32));
+		assert_eq!(status, KittiesStatus::NotOwner as u32);
+		let (status, _) = call_extension(&contract, 5, (0u32, None::<Balance>));
+		assert_eq!(status, KittiesStatus::NotOwner as u32);
+		assert_eq!(Kitties::kitty_prices(0), Some(100));
+	});
+}
+
+#[test]
+fn contract_should_respect_trading_pause() {
+	new_test_ext().execute_with(|| {
+		let contract = deploy();
+		pallet_kitties::Kitties::<Runtime>::insert(&contract, 0, Kitty([1; 16]));
+
+		assert!(Kitties::set_paused(
+			Origin::root(),
+			PauseFlags { minting: false, breeding: false, trading: true }
+		)
+		.is_ok());
+
+		let (status, _) = call_extension(&contract, 4, (&BOB, 0u32));
+		assert_eq!(status, KittiesStatus::TradingPaused as u32);
+		assert!(pallet_kitties::Kitties::<Runtime>::contains_key(&contract, 0));
+	});
+}
@@ -0,0 +1,96 @@
+//! Governs the runtime through council motions.
+
+use codec::Encode;
+use frame_support::{traits::GenesisBuild, weights::GetDispatchInfo};
+use node_template_runtime::{
+	AccountId, Call, Council, Kitties, Origin, RootDispatch, Runtime, System,
+};
+use pallet_kitties::PauseFlags;
+use sp_runtime::{
+	traits::{BlakeTwo256, Hash},
+	AccountId32,
+};
+
+const ALICE: AccountId = AccountId32::new([1; 32]);
+const BOB: AccountId = AccountId32::new([2; 32]);
+const CHARLIE: AccountId = AccountId32::new([3; 32]);
+
+const PAUSED: PauseFlags = PauseFlags { minting: true, breeding: false, trading: false };
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_membership::GenesisConfig::<Runtime, pallet_membership::Instance1> {
+		members: vec![ALICE, BOB, CHARLIE],
+		phantom: Default::default(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Propose `call` with `ALICE`, have the first `ayes` members approve it, and close the motion.
+///
+/// With a single aye the motion is executed right away, as by one member.
+fn pass_motion(call: Call, ayes: u32) {
+	let length = call.encoded_size() as u32;
+	let weight = call.get_dispatch_info().weight;
+	let hash = BlakeTwo256::hash_of(&call);
+	let index = Council::proposal_count();
+
+	// the proposer votes aye
+	assert!(Council::propose(Origin::signed(ALICE), ayes, Box::new(call), length).is_ok());
+	if ayes == 1 {
+		return
+	}
+
+	for member in [BOB, CHARLIE].into_iter().take(ayes as usize - 1) {
+		assert!(Council::vote(Origin::signed(member), hash, index, true).is_ok());
+	}
+	assert!(Council::close(Origin::signed(ALICE), hash, index, weight, length).is_ok());
+}
+
+#[test]
+fn council_majority_should_pause_kitties() {
+	new_test_ext().execute_with(|| {
+		pass_motion(Call::Kitties(pallet_kitties::Call::set_paused { flags: PAUSED }), 2);
+
+		assert_eq!(Kitties::paused(), PAUSED);
+	});
+}
+
+#[test]
+fn council_minority_should_not_pause_kitties() {
+	new_test_ext().execute_with(|| {
+		pass_motion(Call::Kitties(pallet_kitties::Call::set_paused { flags: PAUSED }), 1);
+
+		assert_eq!(Kitties::paused(), PauseFlags::default());
+	});
+}
+
+#[test]
+fn council_majority_should_dispatch_as_root() {
+	new_test_ext().execute_with(|| {
+		let set_storage = Call::System(frame_system::Call::set_storage {
+			items: vec![(b"key".to_vec(), b"value".to_vec())],
+		});
+		let call = Call::RootDispatch(pallet_root_dispatch::Call::dispatch_as_root {
+			call: Box::new(set_storage),
+		});
+
+		pass_motion(call, 2);
+
+		assert_eq!(sp_io::storage::get(b"key"), Some(b"value".to_vec()));
+	});
+}
+
+#[test]
+fn council_member_should_not_dispatch_as_root_alone() {
+	new_test_ext().execute_with(|| {
+		let remark = Call::System(frame_system::Call::remark { remark: vec![] });
+
+		assert!(RootDispatch::dispatch_as_root(Origin::signed(ALICE), Box::new(remark)).is_err());
+	});
+}
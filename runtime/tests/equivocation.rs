@@ -0,0 +1,187 @@
+//! Reports GRANDPA equivocations with session key ownership proofs.
+
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::DispatchResultWithPostInfo,
+	traits::{DisabledValidators, GenesisBuild, KeyOwnerProofSystem, OnFinalize},
+};
+use node_template_runtime::{
+	opaque::SessionKeys, AccountId, Aura, Grandpa, Historical, Origin, Runtime, Session, System,
+};
+use pallet_grandpa::{fg_primitives, AuthorityId as GrandpaId};
+use sp_core::{ed25519, sr25519, Pair, H256};
+use sp_runtime::AccountId32;
+use sp_session::MembershipProof;
+
+const VALIDATORS: [&str; 4] = ["Alice", "Bob", "Charlie", "Dave"];
+
+fn account(index: usize) -> AccountId {
+	AccountId32::new([index as u8 + 1; 32])
+}
+
+fn grandpa_pair(index: usize) -> ed25519::Pair {
+	ed25519::Pair::from_string(&format!("//{}", VALIDATORS[index]), None).unwrap()
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	new_test_ext_with(VALIDATORS.len())
+}
+
+/// The first `validators` of `VALIDATORS` as the genesis validators.
+fn new_test_ext_with(validators: usize) -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	GenesisBuild::<Runtime>::assimilate_storage(
+		&pallet_grandpa::GenesisConfig::default(),
+		&mut storage,
+	)
+	.unwrap();
+	pallet_session::GenesisConfig::<Runtime> {
+		keys: (0..validators)
+			.map(|index| {
+				let aura = sr25519::Pair::from_string(&format!("//{}", VALIDATORS[index]), None)
+					.unwrap()
+					.public();
+				let keys =
+					SessionKeys { aura: aura.into(), grandpa: grandpa_pair(index).public().into() };
+				(account(index), account(index), keys)
+			})
+			.collect(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| {
+		System::initialize(&1, &Default::default(), &Default::default());
+	});
+	ext
+}
+
+/// Two conflicting prevotes of the validator `index` in the current set.
+fn equivocation_proof(index: usize) -> fg_primitives::EquivocationProof<H256, u32> {
+	let pair = grandpa_pair(index);
+	let (set_id, round) = (Grandpa::current_set_id(), 1);
+	let prevote = |target_hash| {
+		let prevote = finality_grandpa::Prevote { target_hash, target_number: 1 };
+		let message = finality_grandpa::Message::Prevote(prevote.clone());
+		let payload = fg_primitives::localized_payload(round, set_id, &message);
+		(prevote, pair.sign(&payload).into())
+	};
+
+	fg_primitives::EquivocationProof::new(
+		set_id,
+		fg_primitives::Equivocation::Prevote(finality_grandpa::Equivocation {
+			round_number: round,
+			identity: pair.public().into(),
+			first: prevote(H256::repeat_byte(1)),
+			second: prevote(H256::repeat_byte(2)),
+		}),
+	)
+}
+
+/// The proof that the validator `index` owns its GRANDPA key in the current session.
+fn key_owner_proof(index: usize) -> MembershipProof {
+	Historical::prove((fg_primitives::KEY_TYPE, GrandpaId::from(grandpa_pair(index).public())))
+		.unwrap()
+}
+
+fn report(index: usize) -> DispatchResultWithPostInfo {
+	Grandpa::report_equivocation_unsigned(
+		Origin::none(),
+		Box::new(equivocation_proof(index)),
+		key_owner_proof(index),
+	)
+}
+
+/// Start the next session in a new block, applying the change of GRANDPA authorities at once.
+fn start_next_session() {
+	let number = System::block_number() + 1;
+	System::initialize(&number, &System::parent_hash(), &Default::default());
+	Session::rotate_session();
+	<Grandpa as OnFinalize<u32>>::on_finalize(number);
+}
+
+#[test]
+fn session_should_set_the_consensus_authorities() {
+	new_test_ext().execute_with(|| {
+		let authorities: Vec<GrandpaId> =
+			Grandpa::grandpa_authorities().into_iter().map(|(id, _)| id).collect();
+
+		assert_eq!(Session::validators(), (0..4).map(account).collect::<Vec<_>>());
+		assert_eq!(
+			authorities,
+			(0..4).map(|index| grandpa_pair(index).public().into()).collect::<Vec<_>>()
+		);
+		assert_eq!(Aura::authorities().len(), 4);
+	});
+}
+
+#[test]
+fn equivocation_should_disable_the_offender() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(report(1));
+
+		assert_eq!(Session::disabled_validators(), vec![1]);
+		assert!(<Runtime as pallet_aura::Config>::DisabledValidators::is_disabled(1));
+		assert!(!<Runtime as pallet_aura::Config>::DisabledValidators::is_disabled(0));
+	});
+}
+
+#[test]
+fn equivocation_should_not_be_reported_twice() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(report(1));
+
+		assert_noop!(report(1), pallet_grandpa::Error::<Runtime>::DuplicateOffenceReport);
+	});
+}
+
+#[test]
+fn offences_should_disable_at_most_a_third_of_the_validators() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(report(1));
+		assert_ok!(report(2));
+
+		assert_eq!(Session::disabled_validators(), vec![1]);
+	});
+}
+
+#[test]
+fn offences_should_disable_one_of_two_validators() {
+	new_test_ext_with(2).execute_with(|| {
+		assert_ok!(report(1));
+		assert_ok!(report(0));
+
+		assert_eq!(Session::disabled_validators(), vec![1]);
+	});
+}
+
+#[test]
+fn offences_should_not_disable_a_lone_validator() {
+	new_test_ext_with(1).execute_with(|| {
+		assert_ok!(report(0));
+
+		assert!(Session::disabled_validators().is_empty());
+	});
+}
+
+#[test]
+fn equivocation_of_past_session_should_be_proven_with_historical_root() {
+	new_test_ext().execute_with(|| {
+		// the genesis sessions have no historical root, see `validators::ValidatorManager`
+		start_next_session();
+		start_next_session();
+		let (equivocation_proof, key_owner_proof) = (equivocation_proof(3), key_owner_proof(3));
+
+		start_next_session();
+		start_next_session();
+		assert_eq!(Session::current_index(), 4);
+
+		assert_ok!(Grandpa::report_equivocation_unsigned(
+			Origin::none(),
+			Box::new(equivocation_proof),
+			key_owner_proof,
+		));
+		assert_eq!(Session::disabled_validators(), vec![3]);
+	});
+}
@@ -0,0 +1,242 @@
+//! Calls the kitties ERC-721 precompile as the EVM would.
+
+use fp_evm::{Context, ExitReason, PrecompileFailure, PrecompileResult, PrecompileSet};
+use frame_support::traits::GenesisBuild;
+use node_template_runtime::{
+	precompiles::{address_of, KittiesAddressMapping, KittiesPrecompiles, KITTIES_ADDRESS},
+	AccountId, FixedGasPrice, Kitties, Origin, Runtime, System,
+};
+use pallet_evm::{AddressMapping, FeeCalculator, Runner};
+use sp_core::{H160, H256, U256};
+
+const ALICE: H160 = H160([1; 20]);
+const BOB: H160 = H160([2; 20]);
+const FORWARDER: H160 = H160([4; 20]);
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![(account(ALICE), 1 << 60), (AccountId::new([3; 32]), 1 << 60)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+fn account(address: H160) -> AccountId {
+	KittiesAddressMapping::into_account_id(address)
+}
+
+fn create_kitty(owner: H160) -> u32 {
+	let kitty_id = Kitties::next_kitty_id();
+	assert!(Kitties::create(Origin::signed(account(owner))).is_ok());
+	kitty_id
+}
+
+fn call(caller: H160, selector: &str, args: &[H256], is_static: bool) -> PrecompileResult {
+	let input = input(selector, args);
+	let address = H160::from_low_u64_be(KITTIES_ADDRESS);
+	let context = Context { address, caller, apparent_value: U256::zero() };
+
+	KittiesPrecompiles::<Runtime>::new()
+		.execute(address, &input, None, &context, is_static)
+		.unwrap()
+}
+
+fn input(selector: &str, args: &[H256]) -> Vec<u8> {
+	let mut input = sp_io::hashing::keccak_256(selector.as_bytes())[..4].to_vec();
+	for arg in args {
+		input.extend_from_slice(arg.as_bytes());
+	}
+	input
+}
+
+fn query(selector: &str, args: &[H256]) -> H256 {
+	H256::from_slice(&call(ALICE, selector, args, true).unwrap().output)
+}
+
+fn word(value: u32) -> H256 {
+	H256::from_low_u64_be(value.into())
+}
+
+#[test]
+fn precompile_should_read_ownership() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty(ALICE);
+
+		assert_eq!(query("balanceOf(address)", &[ALICE.into()]), word(1));
+		assert_eq!(query("balanceOf(address)", &[BOB.into()]), word(0));
+		assert_eq!(query("ownerOf(uint256)", &[word(kitty_id)]), ALICE.into());
+		assert_eq!(query("getApproved(uint256)", &[word(kitty_id)]), H256::zero());
+
+		assert!(matches!(
+			call(ALICE, "ownerOf(uint256)", &[word(kitty_id + 1)], true),
+			Err(PrecompileFailure::Revert { .. })
+		));
+		assert!(matches!(
+			call(ALICE, "balanceOf(address)", &[H256::zero()], true),
+			Err(PrecompileFailure::Revert { .. })
+		));
+	});
+}
+
+#[test]
+fn precompile_should_show_substrate_owners() {
+	new_test_ext().execute_with(|| {
+		let owner = AccountId::new([3; 32]);
+		assert!(Kitties::create(Origin::signed(owner.clone())).is_ok());
+
+		// the owner has no EVM address yet
+		assert!(matches!(
+			call(ALICE, "ownerOf(uint256)", &[word(0)], true),
+			Err(PrecompileFailure::Revert { .. })
+		));
+
+		let address = H160([3; 20]);
+		pallet_evm_accounts::Accounts::<Runtime>::insert(address, &owner);
+		pallet_evm_accounts::Addresses::<Runtime>::insert(&owner, address);
+
+		assert_eq!(query("ownerOf(uint256)", &[word(0)]), address.into());
+		assert_eq!(query("balanceOf(address)", &[address.into()]), word(1));
+		assert_eq!(address_of(&owner), Some(address));
+		assert_eq!(account(address), owner);
+	});
+}
+
+#[test]
+fn precompile_should_transfer_approved_kitty() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty(ALICE);
+
+		// Bob is not approved yet
+		assert!(matches!(
+			call(
+				BOB,
+				"transferFrom(address,address,uint256)",
+				&[ALICE.into(), BOB.into(), word(kitty_id)],
+				false
+			),
+			Err(PrecompileFailure::Revert { .. })
+		));
+
+		let output =
+			call(ALICE, "approve(address,uint256)", &[BOB.into(), word(kitty_id)], false).unwrap();
+		assert_eq!(output.logs.len(), 1);
+		assert_eq!(query("getApproved(uint256)", &[word(kitty_id)]), BOB.into());
+
+		let output = call(
+			BOB,
+			"transferFrom(address,address,uint256)",
+			&[ALICE.into(), BOB.into(), word(kitty_id)],
+			false,
+		)
+		.unwrap();
+		assert_eq!(output.logs[0].topics[1], ALICE.into());
+		assert_eq!(output.logs[0].topics[2], BOB.into());
+
+		assert_eq!(Kitties::owner_of(kitty_id), Some(account(BOB)));
+		assert_eq!(query("balanceOf(address)", &[ALICE.into()]), word(0));
+		assert_eq!(query("getApproved(uint256)", &[word(kitty_id)]), H256::zero());
+	});
+}
+
+#[test]
+fn precompile_should_not_log_transfer_to_owner() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty(ALICE);
+
+		let output = call(
+			ALICE,
+			"transferFrom(address,address,uint256)",
+			&[ALICE.into(), ALICE.into(), word(kitty_id)],
+			false,
+		)
+		.unwrap();
+
+		assert!(output.logs.is_empty());
+		assert_eq!(Kitties::owner_of(kitty_id), Some(account(ALICE)));
+	});
+}
+
+#[test]
+fn precompile_should_not_write_in_static_call() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty(ALICE);
+
+		assert!(matches!(
+			call(ALICE, "approve(address,uint256)", &[BOB.into(), word(kitty_id)], true),
+			Err(PrecompileFailure::Revert { .. })
+		));
+		assert_eq!(Kitties::approvals(kitty_id), None);
+	});
+}
+
+/// Deploy at `FORWARDER` a contract that calls the precompile with its own call data, then
+/// returns, or reverts if `revert` is set.
+fn deploy_forwarder(revert: bool) {
+	let mut code = vec![
+		0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, CALLDATASIZE)
+		0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, // out, in and value
+		0x61, 0x08, 0x00, 0x5a, 0xf1, 0x50, // POP(CALL(GAS, KITTIES_ADDRESS, ...))
+	];
+	if revert {
+		code.extend_from_slice(&[0x60, 0x00, 0x60, 0x00, 0xfd]); // REVERT(0, 0)
+	} else {
+		code.push(0x00); // STOP
+	}
+
+	pallet_evm::AccountCodes::<Runtime>::insert(FORWARDER, code);
+}
+
+fn call_forwarder(selector: &str, args: &[H256]) -> ExitReason {
+	<Runtime as pallet_evm::Config>::Runner::call(
+		ALICE,
+		FORWARDER,
+		input(selector, args),
+		U256::zero(),
+		1_000_000,
+		Some(FixedGasPrice::min_gas_price()),
+		None,
+		None,
+		vec![],
+		<Runtime as pallet_evm::Config>::config(),
+	)
+	.unwrap()
+	.exit_reason
+}
+
+#[test]
+fn precompile_should_write_from_contract() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty(ALICE);
+		assert!(
+			Kitties::transfer(Origin::signed(account(ALICE)), account(FORWARDER), kitty_id).is_ok()
+		);
+		deploy_forwarder(false);
+
+		let exit_reason = call_forwarder("approve(address,uint256)", &[BOB.into(), word(kitty_id)]);
+
+		assert!(matches!(exit_reason, ExitReason::Succeed(_)));
+		assert_eq!(Kitties::approvals(kitty_id), Some(account(BOB)));
+	});
+}
+
+#[test]
+fn precompile_writes_should_revert_with_contract() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = create_kitty(ALICE);
+		assert!(
+			Kitties::transfer(Origin::signed(account(ALICE)), account(FORWARDER), kitty_id).is_ok()
+		);
+		deploy_forwarder(true);
+
+		let exit_reason = call_forwarder("approve(address,uint256)", &[BOB.into(), word(kitty_id)]);
+
+		// the precompile approved Bob, but the revert of its caller undid it
+		assert!(matches!(exit_reason, ExitReason::Revert(_)));
+		assert_eq!(Kitties::approvals(kitty_id), None);
+	});
+}
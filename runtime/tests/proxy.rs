@@ -0,0 +1,106 @@
+//! Acts for an account through kitties proxies.
+
+use frame_support::traits::GenesisBuild;
+use node_template_runtime::{
+	AccountId, Balances, Call, Kitties, Origin, Proxy, ProxyType, Runtime, System,
+};
+use sp_runtime::AccountId32;
+
+const ALICE: AccountId = AccountId32::new([1; 32]);
+const BOB: AccountId = AccountId32::new([2; 32]);
+const CHARLIE: AccountId = AccountId32::new([3; 32]);
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut storage = frame_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	pallet_balances::GenesisConfig::<Runtime> {
+		balances: vec![(ALICE, 1 << 60), (BOB, 1 << 60), (CHARLIE, 1 << 60)],
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Give `ALICE` a kitty and `BOB` a proxy of `proxy_type` for `ALICE`.
+fn setup(proxy_type: ProxyType) -> u32 {
+	let kitty_id = Kitties::next_kitty_id();
+	assert!(Kitties::create(Origin::signed(ALICE)).is_ok());
+	assert!(Proxy::add_proxy(Origin::signed(ALICE), BOB, proxy_type, 0).is_ok());
+	kitty_id
+}
+
+/// Dispatch `call` as `BOB` on behalf of `ALICE`.
+fn proxy(call: Call) {
+	assert!(Proxy::proxy(Origin::signed(BOB), ALICE, None, Box::new(call)).is_ok());
+}
+
+fn transfer_balance() -> Call {
+	Call::Balances(pallet_balances::Call::transfer { dest: CHARLIE.into(), value: 1_000 })
+}
+
+#[test]
+fn trading_proxy_should_set_price_and_buy() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = setup(ProxyType::KittiesTrading);
+
+		proxy(Call::Kitties(pallet_kitties::Call::set_price { kitty_id, new_price: Some(1_000) }));
+		assert_eq!(Kitties::kitty_prices(kitty_id), Some(1_000));
+
+		let other_kitty_id = Kitties::next_kitty_id();
+		assert!(Kitties::create(Origin::signed(CHARLIE)).is_ok());
+		assert!(Kitties::set_price(Origin::signed(CHARLIE), other_kitty_id, Some(1_000)).is_ok());
+
+		proxy(Call::Kitties(pallet_kitties::Call::buy {
+			seller: CHARLIE,
+			kitty_id: other_kitty_id,
+			bid_price: 1_000,
+		}));
+		assert_eq!(Kitties::owner_of(other_kitty_id), Some(ALICE));
+	});
+}
+
+#[test]
+fn trading_proxy_should_not_move_balance_or_kitties() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = setup(ProxyType::KittiesTrading);
+		let balance = Balances::free_balance(&ALICE);
+
+		// the proxy call succeeds, but the filtered call is not dispatched
+		proxy(transfer_balance());
+		proxy(Call::Kitties(pallet_kitties::Call::transfer { to: CHARLIE, kitty_id }));
+
+		assert_eq!(Balances::free_balance(&ALICE), balance);
+		assert_eq!(Kitties::owner_of(kitty_id), Some(ALICE));
+	});
+}
+
+#[test]
+fn kitties_proxy_should_only_make_kitties_calls() {
+	new_test_ext().execute_with(|| {
+		let kitty_id = setup(ProxyType::Kitties);
+		let balance = Balances::free_balance(&ALICE);
+
+		proxy(transfer_balance());
+		assert_eq!(Balances::free_balance(&ALICE), balance);
+
+		proxy(Call::Kitties(pallet_kitties::Call::transfer { to: CHARLIE, kitty_id }));
+		assert_eq!(Kitties::owner_of(kitty_id), Some(CHARLIE));
+	});
+}
+
+#[test]
+fn trading_proxy_should_not_add_proxies() {
+	new_test_ext().execute_with(|| {
+		setup(ProxyType::KittiesTrading);
+
+		proxy(Call::Proxy(pallet_proxy::Call::add_proxy {
+			delegate: CHARLIE,
+			proxy_type: ProxyType::Any,
+			delay: 0,
+		}));
+
+		assert_eq!(Proxy::proxies(&ALICE).0.len(), 1);
+	});
+}
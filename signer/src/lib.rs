@@ -0,0 +1,484 @@
+//! A remote keystore, so that validators can keep their Aura and GRANDPA keys off the node host.
+//!
+//! The node holds a [`RemoteKeystore`], which forwards every keystore operation to a signer over
+//! HTTP, and the `remote-signer` binary serves the keys of a local keystore with [`serve`]. Each
+//! request is a JSON [`Request`] POSTed to the signer URL, and each reply is a JSON [`Reply`].
+//!
+//! Each request carries a shared secret token as `Authorization: Bearer <token>`, and the signer
+//! refuses those without it with a 401. The requests are not encrypted, so the signer must still
+//! only listen on a loopback or private address. It signs with sr25519, ed25519 and ecdsa keys,
+//! but it has no VRF or pre-hashed ecdsa signing, which neither Aura nor GRANDPA use.
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_core::{
+	crypto::{CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData},
+	CryptoStore, Error, SyncCryptoStore,
+};
+use std::{path::Path, time::Duration};
+
+/// How long the keystore waits for a reply of the signer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request to the signer. Key types and cryptos are given by their 4 byte ids, such as `aura`
+/// and `sr25`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Request {
+	/// The public keys of a key type, replied as a list of [`PublicKey`].
+	Keys { key_type: String },
+	/// Generate a key, from the secret URI `seed` if given, replied with its public key.
+	Generate { key_type: String, crypto: String, seed: Option<String> },
+	/// Insert the key of the secret URI `suri`, replied with `null`.
+	Insert { key_type: String, suri: String, public: Bytes },
+	/// Sign `message`, replied with the signature, or `null` if the signer has no such key.
+	Sign { key_type: String, crypto: String, public: Bytes, message: Bytes },
+}
+
+/// The reply of the signer to a [`Request`]: `{"result": ...}` or `{"error": "..."}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply<T> {
+	Result(T),
+	Error(String),
+}
+
+/// A public key held by the signer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKey {
+	pub crypto: String,
+	pub public: Bytes,
+}
+
+/// Read the shared secret token from the file at `path`, ignoring surrounding whitespace.
+pub fn read_token(path: &Path) -> Result<String, String> {
+	let token = std::fs::read_to_string(path)
+		.map_err(|e| format!("Unable to read the token file {}: {}", path.display(), e))?;
+	let token = token.trim();
+	if token.is_empty() {
+		return Err(format!("The token file {} is empty", path.display()))
+	}
+
+	Ok(token.into())
+}
+
+/// Serve the keys of `keystore` to the requests of `server` bearing `token`, until `server` is
+/// unblocked.
+pub fn serve(server: &tiny_http::Server, keystore: &dyn SyncCryptoStore, token: &str) {
+	let json = tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+		.expect("the header is valid; qed");
+	let expected = format!("Bearer {}", token);
+
+	for mut request in server.incoming_requests() {
+		let authorized = request.headers().iter().any(|header| {
+			header.field.equiv("Authorization") &&
+				constant_time_eq(header.value.as_str().as_bytes(), expected.as_bytes())
+		});
+
+		let (status, reply) = if !authorized {
+			(401, Reply::Error("Missing or invalid token".into()))
+		} else {
+			let reply = match serde_json::from_reader(request.as_reader()) {
+				Ok(body) => match handle(keystore, body) {
+					Ok(result) => Reply::Result(result),
+					Err(e) => Reply::Error(e),
+				},
+				Err(e) => Reply::Error(format!("Invalid request: {}", e)),
+			};
+			(200, reply)
+		};
+
+		let body = serde_json::to_string(&reply).expect("replies always serialize; qed");
+		let response = tiny_http::Response::from_string(body)
+			.with_status_code(status)
+			.with_header(json.clone());
+		if let Err(e) = request.respond(response) {
+			log::warn!(target: "remote-signer", "Unable to reply: {}", e);
+		}
+	}
+}
+
+fn handle(keystore: &dyn SyncCryptoStore, request: Request) -> Result<serde_json::Value, String> {
+	let result = match request {
+		Request::Keys { key_type } => {
+			let keys = SyncCryptoStore::keys(keystore, KeyTypeId(parse_id(&key_type)?))
+				.map_err(|e| e.to_string())?;
+			serde_json::to_value(
+				keys.into_iter()
+					.map(|CryptoTypePublicPair(crypto, public)| PublicKey {
+						crypto: format_id(crypto.0),
+						public: public.into(),
+					})
+					.collect::<Vec<_>>(),
+			)
+		},
+		Request::Generate { key_type, crypto, seed } => {
+			let (id, seed) = (KeyTypeId(parse_id(&key_type)?), seed.as_deref());
+			let public = match CryptoTypeId(parse_id(&crypto)?) {
+				sr25519::CRYPTO_ID => SyncCryptoStore::sr25519_generate_new(keystore, id, seed)
+					.map(|public| public.0.to_vec()),
+				ed25519::CRYPTO_ID => SyncCryptoStore::ed25519_generate_new(keystore, id, seed)
+					.map(|public| public.0.to_vec()),
+				ecdsa::CRYPTO_ID => SyncCryptoStore::ecdsa_generate_new(keystore, id, seed)
+					.map(|public| public.0.to_vec()),
+				_ => return Err(format!("Unsupported crypto `{}`", crypto)),
+			}
+			.map_err(|e| e.to_string())?;
+			serde_json::to_value(Bytes(public))
+		},
+		Request::Insert { key_type, suri, public } => {
+			SyncCryptoStore::insert_unknown(
+				keystore,
+				KeyTypeId(parse_id(&key_type)?),
+				&suri,
+				&public,
+			)
+			.map_err(|()| "Unable to insert the key".to_string())?;
+			Ok(serde_json::Value::Null)
+		},
+		Request::Sign { key_type, crypto, public, message } => {
+			let key = CryptoTypePublicPair(CryptoTypeId(parse_id(&crypto)?), public.0);
+			let signature = SyncCryptoStore::sign_with(
+				keystore,
+				KeyTypeId(parse_id(&key_type)?),
+				&key,
+				&message,
+			)
+			.map_err(|e| e.to_string())?;
+			serde_json::to_value(signature.map(Bytes))
+		},
+	};
+
+	result.map_err(|e| e.to_string())
+}
+
+/// A keystore whose keys are held by a remote signer.
+///
+/// Every operation is a blocking HTTP request to the signer. Through [`CryptoStore`] the request
+/// runs on a blocking thread of the Tokio runtime, which the async methods must be called from.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	url: String,
+	authorization: String,
+	agent: ureq::Agent,
+}
+
+impl RemoteKeystore {
+	/// The keystore of the signer listening at `url`, which accepts `token`.
+	pub fn new(url: &str, token: &str) -> Self {
+		Self {
+			url: url.into(),
+			authorization: format!("Bearer {}", token),
+			agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+		}
+	}
+
+	fn call<T: DeserializeOwned>(&self, request: Request) -> Result<T, Error> {
+		let request = serde_json::to_value(request).expect("requests always serialize; qed");
+		let reply = self
+			.agent
+			.post(&self.url)
+			.set("Authorization", &self.authorization)
+			.send_json(request)
+			.map_err(|e| match e {
+				ureq::Error::Status(status, _) =>
+					Error::Other(format!("The signer refused the request with status {}", status)),
+				e => {
+					log::warn!(target: "remote-keystore", "Unable to reach the signer: {}", e);
+					Error::Unavailable
+				},
+			})?
+			.into_json()
+			.map_err(|e| Error::Other(format!("Invalid reply of the signer: {}", e)))?;
+
+		match reply {
+			Reply::Result(result) => Ok(result),
+			Reply::Error(e) => Err(Error::Other(e)),
+		}
+	}
+
+	fn public_keys<P>(&self, id: KeyTypeId, crypto: CryptoTypeId) -> Vec<P>
+	where
+		P: for<'a> TryFrom<&'a [u8]>,
+	{
+		match SyncCryptoStore::keys(self, id) {
+			Ok(keys) => keys
+				.into_iter()
+				.filter(|key| key.0 == crypto)
+				.filter_map(|key| P::try_from(&key.1[..]).ok())
+				.collect(),
+			Err(e) => {
+				log::warn!(target: "remote-keystore", "Unable to list the keys: {}", e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn generate_new<P>(
+		&self,
+		id: KeyTypeId,
+		crypto: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<P, Error>
+	where
+		P: for<'a> TryFrom<&'a [u8]>,
+	{
+		let public: Bytes = self.call(Request::Generate {
+			key_type: format_id(id.0),
+			crypto: format_id(crypto.0),
+			seed: seed.map(Into::into),
+		})?;
+
+		P::try_from(&public[..])
+			.map_err(|_| Error::Other("Invalid public key of the signer".into()))
+	}
+
+	/// Run `f` on a copy of the keystore on a blocking thread, so that its HTTP request does not
+	/// stall the async executor.
+	async fn blocking<R, F>(&self, f: F) -> R
+	where
+		R: Send + 'static,
+		F: FnOnce(&Self) -> R + Send + 'static,
+	{
+		let keystore = self.clone();
+		tokio::task::spawn_blocking(move || f(&keystore))
+			.await
+			.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+	}
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(id, sr25519::CRYPTO_ID)
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		self.generate_new(id, sr25519::CRYPTO_ID, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(id, ed25519::CRYPTO_ID)
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		self.generate_new(id, ed25519::CRYPTO_ID, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(id, ecdsa::CRYPTO_ID)
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		self.generate_new(id, ecdsa::CRYPTO_ID, seed)
+	}
+
+	fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		self.call(Request::Insert {
+			key_type: format_id(id.0),
+			suri: suri.into(),
+			public: public.to_vec().into(),
+		})
+		.map_err(|e| log::warn!(target: "remote-keystore", "Unable to insert the key: {}", e))
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let held = SyncCryptoStore::keys(self, id)?;
+
+		Ok(keys.into_iter().filter(|key| held.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let keys: Vec<PublicKey> = self.call(Request::Keys { key_type: format_id(id.0) })?;
+
+		keys.into_iter()
+			.map(|key| {
+				let crypto = parse_id(&key.crypto).map_err(Error::Other)?;
+				Ok(CryptoTypePublicPair(CryptoTypeId(crypto), key.public.0))
+			})
+			.collect()
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		public_keys.iter().all(|(public, id)| {
+			SyncCryptoStore::keys(self, *id)
+				.map_or(false, |keys| keys.iter().any(|key| &key.1 == public))
+		})
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let signature: Option<Bytes> = self.call(Request::Sign {
+			key_type: format_id(id.0),
+			crypto: format_id(key.0 .0),
+			public: key.1.clone().into(),
+			message: msg.to_vec().into(),
+		})?;
+
+		Ok(signature.map(|signature| signature.0))
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_id: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		Err(Error::Other("The remote signer has no VRF signing".into()))
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		_id: KeyTypeId,
+		_public: &ecdsa::Public,
+		_msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		Err(Error::Other("The remote signer has no pre-hashed ecdsa signing".into()))
+	}
+}
+
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::sr25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::sr25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ed25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ed25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ecdsa_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let (suri, public) = (suri.to_string(), public.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::insert_unknown(keystore, id, &suri, &public))
+			.await
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::supported_keys(keystore, id, keys))
+			.await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::keys(keystore, id)).await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |keystore| SyncCryptoStore::has_keys(keystore, &public_keys))
+			.await
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::sign_with(keystore, id, &key, &msg))
+			.await
+	}
+
+	// the signer has neither of the next two, so they make no request to move off the executor
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		SyncCryptoStore::sr25519_vrf_sign(self, key_type, public, transcript_data)
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		SyncCryptoStore::ecdsa_sign_prehashed(self, id, public, msg)
+	}
+}
+
+/// The 4 byte id of a key type or crypto, such as `aura`.
+fn format_id(id: [u8; 4]) -> String {
+	String::from_utf8_lossy(&id).into_owned()
+}
+
+/// Whether `a` equals `b`, in a time that does not depend on where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn parse_id(id: &str) -> Result<[u8; 4], String> {
+	id.as_bytes().try_into().map_err(|_| format!("`{}` is not a 4 byte id", id))
+}
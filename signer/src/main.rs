@@ -0,0 +1,39 @@
+//! A reference signer for the remote keystore of the node, serving the keys of a local keystore.
+
+use clap::Parser;
+use sc_keystore::LocalKeystore;
+use std::{error::Error, net::SocketAddr, path::PathBuf};
+
+#[derive(Debug, Parser)]
+struct Cli {
+	/// The address to listen on. Requests are not encrypted, so only listen on a loopback or
+	/// private address.
+	#[clap(long, default_value = "127.0.0.1:9955")]
+	listen: SocketAddr,
+
+	/// The file holding the shared secret token that the node sends with every request, which
+	/// it reads from its `--keystore-token-file`.
+	#[clap(long, parse(from_os_str))]
+	token_file: PathBuf,
+
+	/// The keystore directory, with the layout of the keystore of a node. Keys are only kept in
+	/// memory if not given.
+	#[clap(long, parse(from_os_str))]
+	keystore_path: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+	let cli = Cli::parse();
+
+	let keystore = match &cli.keystore_path {
+		Some(path) => LocalKeystore::open(path, None)?,
+		None => LocalKeystore::in_memory(),
+	};
+	let token = remote_signer::read_token(&cli.token_file)?;
+	let server = tiny_http::Server::http(cli.listen)?;
+
+	eprintln!("Serving the keys on http://{}", cli.listen);
+	remote_signer::serve(&server, &keystore, &token);
+
+	Ok(())
+}
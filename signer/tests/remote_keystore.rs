@@ -0,0 +1,112 @@
+//! Uses a remote keystore against the reference signer.
+
+use remote_signer::RemoteKeystore;
+use sc_keystore::LocalKeystore;
+use sp_core::{
+	crypto::{key_types::GRANDPA, CryptoTypePublicPair, KeyTypeId},
+	ed25519, sr25519, Pair,
+};
+use sp_keystore::{CryptoStore, SyncCryptoStore};
+
+const AURA: KeyTypeId = KeyTypeId(*b"aura");
+const TOKEN: &str = "secret";
+
+/// Start a signer with an empty keystore, and connect a remote keystore with `token` to it.
+fn remote_keystore_with_token(token: &str) -> RemoteKeystore {
+	let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+	let url = format!("http://{}", server.server_addr());
+	std::thread::spawn(move || remote_signer::serve(&server, &LocalKeystore::in_memory(), TOKEN));
+
+	RemoteKeystore::new(&url, token)
+}
+
+fn remote_keystore() -> RemoteKeystore {
+	remote_keystore_with_token(TOKEN)
+}
+
+#[test]
+fn should_sign_with_generated_sr25519_key() {
+	let keystore = remote_keystore();
+
+	let public = SyncCryptoStore::sr25519_generate_new(&keystore, AURA, None).unwrap();
+	assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, AURA), vec![public]);
+	assert!(SyncCryptoStore::sr25519_public_keys(&keystore, GRANDPA).is_empty());
+	assert!(SyncCryptoStore::has_keys(&keystore, &[(public.0.to_vec(), AURA)]));
+
+	let signature = SyncCryptoStore::sign_with(&keystore, AURA, &public.into(), b"block")
+		.unwrap()
+		.expect("the signer holds the key");
+	let signature = sr25519::Signature::try_from(&signature[..]).unwrap();
+	assert!(sr25519::Pair::verify(&signature, b"block", &public));
+}
+
+#[test]
+fn should_sign_with_inserted_ed25519_key() {
+	let keystore = remote_keystore();
+	let pair = ed25519::Pair::from_string("//Alice", None).unwrap();
+	let public = pair.public();
+
+	assert!(!SyncCryptoStore::has_keys(&keystore, &[(public.0.to_vec(), GRANDPA)]));
+	SyncCryptoStore::insert_unknown(&keystore, GRANDPA, "//Alice", &public.0).unwrap();
+	assert!(SyncCryptoStore::has_keys(&keystore, &[(public.0.to_vec(), GRANDPA)]));
+	assert_eq!(SyncCryptoStore::ed25519_public_keys(&keystore, GRANDPA), vec![public]);
+
+	let key = CryptoTypePublicPair::from(public);
+	assert_eq!(
+		SyncCryptoStore::supported_keys(&keystore, GRANDPA, vec![key.clone()]).unwrap(),
+		vec![key.clone()]
+	);
+
+	let signature = SyncCryptoStore::sign_with(&keystore, GRANDPA, &key, b"vote")
+		.unwrap()
+		.expect("the signer holds the key");
+	assert_eq!(signature, pair.sign(b"vote").0.to_vec());
+}
+
+#[test]
+fn should_not_sign_with_unknown_key() {
+	let keystore = remote_keystore();
+	let public = sr25519::Pair::from_string("//Bob", None).unwrap().public();
+
+	assert_eq!(
+		SyncCryptoStore::sign_with(&keystore, AURA, &public.into(), b"block").unwrap(),
+		None
+	);
+}
+
+#[tokio::test]
+async fn should_sign_through_async_keystore() {
+	let keystore = remote_keystore();
+
+	let public = CryptoStore::sr25519_generate_new(&keystore, AURA, None).await.unwrap();
+	assert_eq!(CryptoStore::sr25519_public_keys(&keystore, AURA).await, vec![public]);
+
+	let signature = CryptoStore::sign_with(&keystore, AURA, &public.into(), b"block")
+		.await
+		.unwrap()
+		.expect("the signer holds the key");
+	let signature = sr25519::Signature::try_from(&signature[..]).unwrap();
+	assert!(sr25519::Pair::verify(&signature, b"block", &public));
+}
+
+#[test]
+fn should_refuse_wrong_token() {
+	let keystore = remote_keystore_with_token("guess");
+
+	assert!(matches!(
+		SyncCryptoStore::sr25519_generate_new(&keystore, AURA, None),
+		Err(sp_keystore::Error::Other(_))
+	));
+	assert!(SyncCryptoStore::sr25519_public_keys(&keystore, AURA).is_empty());
+}
+
+#[test]
+fn should_be_unavailable_without_signer() {
+	let keystore = RemoteKeystore::new("http://127.0.0.1:1", TOKEN);
+
+	assert!(matches!(
+		SyncCryptoStore::sr25519_generate_new(&keystore, AURA, None),
+		Err(sp_keystore::Error::Unavailable)
+	));
+	assert!(SyncCryptoStore::sr25519_public_keys(&keystore, AURA).is_empty());
+}